@@ -0,0 +1,115 @@
+use std::{env, fs, process};
+use std::time::Instant;
+
+use mchess::board::Board;
+use mchess::perft::{self, EpdPosition, PerftOptions};
+use mchess::pieces::attacks;
+
+const USAGE: &str = "usage: perft [--fen <fen>] [--depth <n>] [--threads <n>] [--hash <mb>] [--epd <file>]
+
+Prints the node count below every legal move of the position. With --epd every position of the
+file is checked against its reference counts, --depth then caps the depths that are run.";
+
+struct Args {
+    fen: Option<String>,
+    depth: Option<u32>,
+    epd: Option<String>,
+    options: PerftOptions
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        fen: None,
+        depth: None,
+        epd: None,
+        options: PerftOptions::default()
+    };
+
+    let mut rest = env::args().skip(1);
+    while let Some(arg) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| format!("Missing value for {}", arg));
+
+        match arg.as_str() {
+            "--fen" => args.fen = Some(value()?),
+            "--depth" => args.depth = Some(value()?.parse().map_err(|_| "Invalid depth".to_string())?),
+            "--threads" => args.options.threads = value()?.parse().map_err(|_| "Invalid thread count".to_string())?,
+            "--hash" => args.options.hash_mb = value()?.parse().map_err(|_| "Invalid hash size".to_string())?,
+            "--epd" => args.epd = Some(value()?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("Unknown argument {}\n\n{}", other, USAGE))
+        }
+    }
+
+    Ok(args)
+}
+
+fn run_divide(fen: Option<&str>, depth: u32, options: &PerftOptions) -> Result<(), String> {
+    let board = match fen {
+        Some(fen) => Board::try_from_fen(fen).map_err(|e| format!("Invalid FEN: {}", e))?,
+        None => Board::startpos()
+    };
+
+    let start = Instant::now();
+    let counts = perft::divide(&board, depth, options);
+    let elapsed = start.elapsed();
+
+    print!("{}", perft::format_divide(&board, &counts));
+
+    let nodes: u64 = counts.iter().map(|(_, nodes)| nodes).sum();
+    println!("Time: {} ms ({:.0} nps)", elapsed.as_millis(), nodes as f64 / elapsed.as_secs_f64().max(1e-9));
+
+    Ok(())
+}
+
+// returns whether every count matched
+fn run_epd(path: &str, max_depth: Option<u32>, options: &PerftOptions) -> Result<bool, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+    let mut failures = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let position = match EpdPosition::parse(line) {
+            Ok(position) => position,
+            Err(e) => {
+                println!("line {}: {}", i + 1, e);
+                failures += 1;
+                continue;
+            }
+        };
+
+        let mismatches = position.check(max_depth, options);
+        if mismatches.is_empty() {
+            println!("line {}: ok", i + 1);
+        } else {
+            failures += 1;
+            for mismatch in mismatches {
+                println!("line {}: {} depth {} expected {} found {}", i + 1, position.fen, mismatch.depth, mismatch.expected, mismatch.found);
+            }
+        }
+    }
+
+    println!("\n{} position(s) failed", failures);
+
+    Ok(failures == 0)
+}
+
+fn main() {
+    attacks::init();
+
+    let result = parse_args().and_then(|args| match &args.epd {
+        Some(path) => run_epd(path, args.depth, &args.options),
+        None => run_divide(args.fen.as_deref(), args.depth.unwrap_or(5), &args.options).map(|_| true)
+    });
+
+    match result {
+        Ok(true) => {},
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
@@ -1,48 +1,205 @@
 use core::fmt;
-use std::collections::HashSet;
-use std::{collections::HashMap, i64};
+use std::collections::HashMap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::r#const::{MAX_PHASE, MOBILITY_VALUE, MOVE_PREALLOC};
+use crate::r#const::{MAX_PHASE, MAX_PIECES, MOBILITY_VALUE, PIECE_VALUES};
 use crate::piece::{BasePiece, PartialPiece, Piece, PieceColor, PieceType};
-use crate::moves::{Move, MoveType, Pin, Position, Vector};
+use crate::movegen;
+use crate::moves::{Move, MoveList, MoveType, PackedMove, Pin, Position, Vector, PIECE_TYPES};
+use crate::pieces::attacks;
 use crate::pieces::bishop::{get_controlled_squares_bishop, get_legal_moves_bishop, get_pins_bishop};
-use crate::pieces::bitboard::COLOR_MASK;
-use crate::pieces::king::{get_controlled_squares_king, get_legal_moves_king};
+use crate::pieces::bitboard::{COLOR_MASK, RANK_1, RANK_8};
+use crate::pieces::king::{get_castling_move, get_controlled_squares_king, get_legal_moves_king};
 use crate::pieces::knight::{get_controlled_squares_knight, get_legal_moves_knight};
 use crate::pieces::pawn::{get_controlled_squares_pawn, get_legal_moves_pawn};
 use crate::pieces::queen::{get_controlled_squares_queen, get_legal_moves_queen, get_pins_queen};
 use crate::pieces::rook::{get_controlled_squares_rook, get_legal_moves_rook, get_pins_rook};
+use crate::render;
+use crate::zobrist;
 
-use rand::rngs::StdRng;
-use rand::{SeedableRng, Rng};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DrawReason {
+    InsufficientMaterial,
+    FiftyMoves,
+    SeventyFiveMoves,
+    ThreefoldRepetition,
+    FivefoldRepetition
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawReason::InsufficientMaterial => write!(f, "insufficient material"),
+            DrawReason::FiftyMoves => write!(f, "fifty-move rule"),
+            DrawReason::SeventyFiveMoves => write!(f, "seventy-five-move rule"),
+            DrawReason::ThreefoldRepetition => write!(f, "threefold repetition"),
+            DrawReason::FivefoldRepetition => write!(f, "fivefold repetition")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ResultType {
     WhiteCheckmate,
     BlackCheckmate,
     Stalemate,
-    Draw,
+    Draw(DrawReason),
+    // the game continues unless a player claims the draw
+    Claimable(DrawReason),
     None,
     NotCached,
 }
 
 impl ResultType {
-    pub fn is_end(&self) -> bool{
-        if self == &ResultType::None || self == &ResultType::NotCached {
-            false
-        } else {
-            true
+    pub fn is_end(&self) -> bool {
+        !matches!(self, ResultType::None | ResultType::NotCached | ResultType::Claimable(_))
+    }
+
+    pub fn is_draw(&self) -> bool {
+        matches!(self, ResultType::Stalemate | ResultType::Draw(_) | ResultType::Claimable(_))
+    }
+
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            ResultType::WhiteCheckmate => "1-0",
+            ResultType::BlackCheckmate => "0-1",
+            ResultType::Stalemate | ResultType::Draw(_) => "1/2-1/2",
+            _ => "*"
+        }
+    }
+}
+
+impl fmt::Display for ResultType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResultType::WhiteCheckmate => write!(f, "white wins by checkmate"),
+            ResultType::BlackCheckmate => write!(f, "black wins by checkmate"),
+            ResultType::Stalemate => write!(f, "draw by stalemate"),
+            ResultType::Draw(reason) => write!(f, "draw by {}", reason),
+            ResultType::Claimable(reason) => write!(f, "draw can be claimed by {}", reason),
+            ResultType::None | ResultType::NotCached => write!(f, "game in progress")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    InvalidFieldCount(usize),
+    InvalidRankCount(usize),
+    InvalidRankLength(usize),
+    InvalidPiece(char),
+    MissingKing(PieceColor),
+    TooManyKings(PieceColor),
+    InvalidTurn(String),
+    InvalidCastlingRights(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidMoveNumber(String),
+    OpponentInCheck
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::InvalidFieldCount(count) => write!(f, "expected 6 fields, found {}", count),
+            FenError::InvalidRankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenError::InvalidRankLength(rank) => write!(f, "rank {} does not have 8 squares", 8 - rank),
+            FenError::InvalidPiece(c) => write!(f, "invalid piece character '{}'", c),
+            FenError::MissingKing(color) => write!(f, "{:?} king is missing", color),
+            FenError::TooManyKings(color) => write!(f, "{:?} has more than one king", color),
+            FenError::InvalidTurn(turn) => write!(f, "invalid side to move '{}'", turn),
+            FenError::InvalidCastlingRights(c) => write!(f, "invalid castling rights '{}'", c),
+            FenError::InvalidEnPassant(t) => write!(f, "impossible en passant square '{}'", t),
+            FenError::InvalidHalfmoveClock(h) => write!(f, "invalid halfmove clock '{}'", h),
+            FenError::InvalidMoveNumber(m) => write!(f, "invalid move number '{}'", m),
+            FenError::OpponentInCheck => write!(f, "side not to move is in check")
         }
     }
 }
 
+impl std::error::Error for FenError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PositionError {
+    MissingKing(PieceColor),
+    TooManyKings(PieceColor),
+    PawnOnBackRank(Position),
+    TooManyPieces(PieceColor, usize),
+    TooManyPawns(PieceColor, usize),
+    ImpossiblePromotions(PieceColor),
+    OpponentInCheck,
+    KingsAdjacent,
+    InvalidCastlingRights(PieceColor, bool),
+    InvalidEnPassant(Position)
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::MissingKing(color) => write!(f, "{:?} king is missing", color),
+            PositionError::TooManyKings(color) => write!(f, "{:?} has more than one king", color),
+            PositionError::PawnOnBackRank(pos) => write!(f, "pawn on the back rank at {:?}", pos),
+            PositionError::TooManyPieces(color, count) => write!(f, "{:?} has {} pieces, at most 16 are possible", color, count),
+            PositionError::TooManyPawns(color, count) => write!(f, "{:?} has {} pawns, at most 8 are possible", color, count),
+            PositionError::ImpossiblePromotions(color) => write!(f, "{:?} has more promoted pieces than missing pawns", color),
+            PositionError::OpponentInCheck => write!(f, "side not to move is in check"),
+            PositionError::KingsAdjacent => write!(f, "kings are on adjacent squares"),
+            PositionError::InvalidCastlingRights(color, kingside) => write!(f, "{:?} has {} castling rights without king and rook on their home squares",
+                color, if *kingside { "kingside" } else { "queenside" }),
+            PositionError::InvalidEnPassant(pos) => write!(f, "en passant square {:?} does not follow a double pawn push", pos)
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
 #[derive(Debug, Clone)]
 pub struct Castling {
     pub white: (bool, bool),
     pub black: (bool, bool),
+    // files of the kingside and queenside castling rooks
+    pub white_rooks: (usize, usize),
+    pub black_rooks: (usize, usize)
 }
 
 impl Castling {
+    pub fn new(white: (bool, bool), black: (bool, bool)) -> Castling {
+        Castling { white, black, white_rooks: (7, 0), black_rooks: (7, 0) }
+    }
+
+    pub fn can_castle(&self, color: PieceColor, kingside: bool) -> bool {
+        if kingside {
+            self.can_castle_ks(color)
+        } else {
+            self.can_castle_qs(color)
+        }
+    }
+
+    pub fn rook_file(&self, color: PieceColor, kingside: bool) -> usize {
+        let rooks = match color {
+            PieceColor::White => self.white_rooks,
+            PieceColor::Black => self.black_rooks
+        };
+
+        if kingside { rooks.0 } else { rooks.1 }
+    }
+
+    pub fn set(&mut self, color: PieceColor, kingside: bool, rook_file: Option<usize>) {
+        let (rights, rooks) = match color {
+            PieceColor::White => (&mut self.white, &mut self.white_rooks),
+            PieceColor::Black => (&mut self.black, &mut self.black_rooks)
+        };
+
+        if kingside {
+            rights.0 = rook_file.is_some();
+            rooks.0 = rook_file.unwrap_or(rooks.0);
+        } else {
+            rights.1 = rook_file.is_some();
+            rooks.1 = rook_file.unwrap_or(rooks.1);
+        }
+    }
+
     pub fn can_castle_ks(&self, color: PieceColor) -> bool {
         match color {
             PieceColor::White => self.white.0,
@@ -118,6 +275,33 @@ pub struct ControlTableEntry {
 }
 
 impl ControlTableEntry {
+    fn push_moves(&self, board: &Board, position: Position, is_en_passant: bool, moves: &mut MoveList) {
+        let from = self.origin.pos;
+        let piece_type = self.origin.piece_type;
+
+        if is_en_passant {
+            moves.push(PackedMove::new(from, board.target_square.unwrap(), PackedMove::EN_PASSANT, piece_type, Some(PieceType::Pawn)));
+            return;
+        }
+
+        let captured = if self.control_type == ControlType::Attack {
+            board.get_piece_at_bitboard(position.to_bitboard()).map(|(t, _)| t)
+        } else {
+            None
+        };
+        let capture_flag = if captured.is_some() { PackedMove::CAPTURE } else { PackedMove::QUIET };
+
+        if piece_type == PieceType::Pawn && (position.y == 0 || position.y == 7) {
+            for promotion in (0..4).rev() {
+                moves.push(PackedMove::new(from, position, PackedMove::PROMOTION | capture_flag | promotion, piece_type, captured));
+            }
+        } else if piece_type == PieceType::Pawn && from.y.abs_diff(position.y) == 2 {
+            moves.push(PackedMove::new(from, position, PackedMove::DOUBLE_PUSH, piece_type, None));
+        } else {
+            moves.push(PackedMove::new(from, position, capture_flag, piece_type, captured));
+        }
+    }
+
     pub fn to_move(&self, board: &Board, position: Position, is_en_passant: bool) -> Move {
         Move {
             from: self.origin.pos,
@@ -168,7 +352,7 @@ impl ControlBitboards {
 
 #[derive(Clone)]
 pub struct MoveInfo {
-    pub hash: i64,
+    pub hash: u64,
     pub captured_piece: Option<Piece>,
     pub halfmove_clock: i32,
     pub white_check: CheckInfo,
@@ -176,10 +360,10 @@ pub struct MoveInfo {
     pub turn: PieceColor,
     pub castling: Castling,
     pub promoted_type: Option<PieceType>,
-    pub control_bitboards: ControlBitboards,
     pub target_square: Option<Position>,
     pub target_piece: i32,
-    pub bitboards: BitboardData
+    pub bitboards: BitboardData,
+    pub hash_history: Vec<u64>
 }
 
 #[derive(Clone)]
@@ -203,26 +387,43 @@ pub struct BitboardData {
     pub empty_squares: u64,
 }
 
+impl BitboardData {
+    // one set per piece type, in PieceType::index order
+    pub fn pieces_of(&self, color: PieceColor) -> [u64; 6] {
+        if color == PieceColor::White {
+            [self.white_pawns, self.white_knights, self.white_bishops, self.white_rooks, self.white_queens, self.white_king]
+        } else {
+            [self.black_pawns, self.black_knights, self.black_bishops, self.black_rooks, self.black_queens, self.black_king]
+        }
+    }
+
+    pub fn pieces_of_color(&self, color: PieceColor) -> u64 {
+        if color == PieceColor::White { self.white_pieces } else { self.black_pieces }
+    }
+}
+
 #[derive(Clone)]
 pub struct Board {
     pub bb: BitboardData,
-    pub board: Vec<Vec<isize>>,
-    pub pin_table: Vec<Vec<Vec<Pin>>>,
-    pub pieces: HashMap<usize, Piece>,
+    // indexed by x + y * 8 like the bitboards
+    pub mailbox: [Option<Piece>; 64],
+    // square of every piece by its index, None once captured
+    pub piece_list: [Option<usize>; MAX_PIECES],
     pub moves: i32,
     pub halfmove_clock: i32,
     pub turn: PieceColor,
     pub castling: Castling,
+    pub chess960: bool,
     pub target_square: Option<Position>,
     pub target_piece: i32,
     pub result_cache: ResultType,
-    pub total_moves_cache: HashMap<PieceColor, Vec<Move>>,
+    pub total_moves_cache: HashMap<PieceColor, MoveList>,
     pub moves_cache: HashMap<usize, Vec<Move>>,
     pub move_availability: HashMap<usize, bool>,
     pub white_check: CheckInfo,
     pub black_check: CheckInfo,
-    pub hash_table: Vec<i64>,
-    pub hash: i64,
+    pub hash: u64,
+    pub hash_history: Vec<u64>,
     pub mobility_cache: HashMap<usize, f64>,
 
     pub control_bitboards: ControlBitboards
@@ -251,8 +452,8 @@ impl Board {
                 all_pieces: 0,
                 empty_squares: !0
             },
-            board: vec![vec![-1; 8]; 9],
-            pieces: HashMap::new(),
+            mailbox: [None; 64],
+            piece_list: [None; MAX_PIECES],
             moves: match moves {
                 Some(a) => a,
                 None => 1
@@ -267,22 +468,19 @@ impl Board {
             },
             castling: match castling {
                 Some(a) => a,
-                None => Castling {
-                    white: (true, true),
-                    black: (true, true)
-                }
+                None => Castling::new((true, true), (true, true))
             },
+            chess960: false,
             target_square,
             target_piece: -1,
-            pin_table: vec![vec![vec![]; 8]; 8],
             result_cache: ResultType::NotCached,
             total_moves_cache: HashMap::new(),
             moves_cache: HashMap::new(),
             move_availability: HashMap::new(),
             white_check: CheckInfo::default(),
             black_check: CheckInfo::default(),
-            hash_table: Vec::with_capacity(782),
-            hash: i64::MAX,
+            hash: 0,
+            hash_history: Vec::new(),
             mobility_cache: HashMap::new(),
 
             control_bitboards: ControlBitboards { 
@@ -299,8 +497,24 @@ impl Board {
     }
 
     pub fn from_fen(fen: &str) -> Board {
+        match Board::parse_fen(fen, false) {
+            Ok(board) => board,
+            Err(e) => panic!("Invalid FEN {}: {}", fen, e)
+        }
+    }
+
+    pub fn try_from_fen(fen: &str) -> Result<Board, FenError> {
+        Board::parse_fen(fen, true)
+    }
+
+    // non-strict parsing only rejects malformed input, strict parsing also rejects illegal positions
+    fn parse_fen(fen: &str, strict: bool) -> Result<Board, FenError> {
         let mut board = Board::new(None, None, None, None, None);
         let parts: Vec<&str> = fen.split_whitespace().collect();
+        if parts.len() != 6 {
+            return Err(FenError::InvalidFieldCount(parts.len()));
+        }
+
         let position = parts[0];
         let turn = parts[1];
         let c = parts[2];
@@ -309,76 +523,389 @@ impl Board {
         let moves = parts[5];
 
         let ranks: Vec<&str> = position.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::InvalidRankCount(ranks.len()));
+        }
 
         for (j, rank) in ranks.iter().enumerate() {
             let mut i = 0;
-            for char in rank.chars().into_iter() {
-                if char.is_digit(10) {
-                    i += char.to_digit(10).unwrap() as usize - 1;
+            for char in rank.chars() {
+                if let Some(empty) = char.to_digit(10) {
+                    if !(1..=8).contains(&empty) {
+                        return Err(FenError::InvalidPiece(char));
+                    }
+                    i += empty as usize;
+                    if i > 8 {
+                        return Err(FenError::InvalidRankLength(j));
+                    }
+                    continue;
+                }
+
+                let piece_type = match char.to_ascii_lowercase() {
+                    'p' => PieceType::Pawn,
+                    'n' => PieceType::Knight,
+                    'b' => PieceType::Bishop,
+                    'r' => PieceType::Rook,
+                    'q' => PieceType::Queen,
+                    'k' => PieceType::King,
+                    _ => return Err(FenError::InvalidPiece(char))
+                };
+
+                if i >= 8 {
+                    return Err(FenError::InvalidRankLength(j));
+                }
+
+                let color = if char.is_ascii_uppercase() {
+                    PieceColor::White
                 } else {
-                    let color = if "PNBRQK".contains(char) {
-                        PieceColor::White
-                    } else {
-                        PieceColor::Black
-                    };
+                    PieceColor::Black
+                };
 
-                    let index = board.pieces.len();
-
-                    let piece: Piece = Piece {
-                        piece_type: match char.to_ascii_lowercase() {
-                            'p' => PieceType::Pawn,
-                            'n' => PieceType::Knight,
-                            'b' => PieceType::Bishop,
-                            'r' => PieceType::Rook,
-                            'q' => PieceType::Queen,
-                            'k' => PieceType::King,
-                            _ => panic!("Invalid piece type")
-                        },
-                        color,
-                        pos: Position { x: i, y: j },
-                        index,
-                        legal_moves_cache: vec![],
-                        legal_moves: true
-                    };
+                let index = board.next_piece_index();
 
-                    board.board[i][j] = board.pieces.len() as isize;
-                    board.pieces.insert(index, piece.clone());
+                let piece: Piece = Piece {
+                    piece_type,
+                    color,
+                    pos: Position { x: i, y: j },
+                    index
+                };
+
+                board.bb_or_pos(piece.get_base(), piece.pos);
+                board.place_piece(piece);
 
-                    board.bb_or_pos(piece.get_base(), piece.pos);
-                }
                 i += 1;
             }
+
+            if i != 8 {
+                return Err(FenError::InvalidRankLength(j));
+            }
         }
 
-        if board.bb.white_king == 0u64 || board.bb.black_king == 0u64 {
-            panic!("Invalid chess board");
+        for (color, king) in [(PieceColor::White, board.bb.white_king), (PieceColor::Black, board.bb.black_king)] {
+            match king.count_ones() {
+                0 => return Err(FenError::MissingKing(color)),
+                1 => {},
+                _ => return Err(FenError::TooManyKings(color))
+            }
+        }
+
+        board.turn = match turn {
+            "w" => PieceColor::White,
+            "b" => PieceColor::Black,
+            _ => return Err(FenError::InvalidTurn(turn.to_string()))
+        };
+
+        board.castling = board.parse_castling(c, strict)?;
+        board.chess960 = !board.is_standard_castling();
+        board.halfmove_clock = halfmoves.parse().map_err(|_| FenError::InvalidHalfmoveClock(halfmoves.to_string()))?;
+        board.moves = moves.parse().map_err(|_| FenError::InvalidMoveNumber(moves.to_string()))?;
+        if board.halfmove_clock < 0 {
+            return Err(FenError::InvalidHalfmoveClock(halfmoves.to_string()));
+        }
+        if board.moves < 1 {
+            return Err(FenError::InvalidMoveNumber(moves.to_string()));
         }
 
-        board.turn = if turn == "b" { PieceColor::Black } else { PieceColor::White };
-        board.castling.white = (c.contains("K"), c.contains("Q"));
-        board.castling.black = (c.contains("k"), c.contains("q"));
-        board.halfmove_clock = halfmoves.parse().unwrap();
-        board.moves = moves.parse().unwrap();
-        
         if target_square != "-" {
-            board.target_square = Some(Position {
-                x: "abcdefgh".find(target_square.chars().next().unwrap()).unwrap(),
-                y: 8 - target_square[1..].parse::<usize>().unwrap()
-            })
+            let target = board.parse_en_passant(target_square, strict)?;
+            board.target_square = Some(target);
+
+            // the pawn that made the double push, needed to spot en passant captures that expose the king
+            let pawn_y = if target.y == 2 { 3 } else { 4 };
+            if let Some(pawn) = board.get_piece_at(pawn_y, target.x).filter(|p| p.piece_type == PieceType::Pawn) {
+                board.target_piece = pawn.index as i32;
+            }
         }
 
         board.gen_hash();
 
         board.check_control_all();
-        board.update_pins();
 
-        board
+        if strict && board.get_check(board.turn.opposite()).checked != 0u64 {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        Ok(board)
+    }
+
+    fn parse_castling(&self, c: &str, strict: bool) -> Result<Castling, FenError> {
+        let mut castling = Castling::new((false, false), (false, false));
+
+        if c == "-" {
+            return Ok(castling);
+        }
+
+        let err = || FenError::InvalidCastlingRights(c.to_string());
+
+        // accepts KQkq (X-FEN, the outermost rook on that side) as well as Shredder-FEN rook files
+        for char in c.chars() {
+            let color = if char.is_ascii_uppercase() { PieceColor::White } else { PieceColor::Black };
+            let rank = if color == PieceColor::White { 7 } else { 0 };
+
+            let is_piece = |file: usize, piece_type: PieceType| {
+                self.get_piece_at(rank, file).is_some_and(|p| p.piece_type == piece_type && p.color == color)
+            };
+            let king_file = (0..8).find(|&file| is_piece(file, PieceType::King));
+
+            if strict && king_file.is_none() {
+                return Err(err());
+            }
+
+            let (kingside, rook_file, default_file) = match char.to_ascii_lowercase() {
+                'k' => (true, king_file.and_then(|k| ((k + 1)..8).rev().find(|&f| is_piece(f, PieceType::Rook))), 7),
+                'q' => (false, king_file.and_then(|k| (0..k).find(|&f| is_piece(f, PieceType::Rook))), 0),
+                file @ 'a'..='h' => {
+                    let file = file as usize - 'a' as usize;
+                    (king_file.map_or(file >= 4, |k| file > k), Some(file).filter(|&f| is_piece(f, PieceType::Rook)), file)
+                },
+                _ => return Err(err())
+            };
+
+            match rook_file {
+                Some(file) => castling.set(color, kingside, Some(file)),
+                None if strict => return Err(err()),
+                None => castling.set(color, kingside, Some(default_file))
+            }
+        }
+
+        Ok(castling)
+    }
+
+    fn is_standard_castling(&self) -> bool {
+        [(PieceColor::White, 7), (PieceColor::Black, 0)].iter().all(|&(color, rank)| {
+            let has_right = self.castling.can_castle_ks(color) || self.castling.can_castle_qs(color);
+            let king_on_e = self.get_piece_at(rank, 4).is_some_and(|p| p.piece_type == PieceType::King && p.color == color);
+
+            !has_right || (king_on_e &&
+                (!self.castling.can_castle_ks(color) || self.castling.rook_file(color, true) == 7) &&
+                (!self.castling.can_castle_qs(color) || self.castling.rook_file(color, false) == 0))
+        })
+    }
+
+    fn parse_en_passant(&self, target_square: &str, strict: bool) -> Result<Position, FenError> {
+        let err = || FenError::InvalidEnPassant(target_square.to_string());
+
+        let bytes = target_square.as_bytes();
+        if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) {
+            return Err(err());
+        }
+
+        let x = (bytes[0] - b'a') as usize;
+        if !strict {
+            if !(b'1'..=b'8').contains(&bytes[1]) {
+                return Err(err());
+            }
+            return Ok(Position { x, y: (b'8' - bytes[1]) as usize });
+        }
+
+        // the pawn that just moved belongs to the side not to move
+        let (y, pawn_y, origin_y) = match (bytes[1], self.turn) {
+            (b'6', PieceColor::White) => (2, 3, 1),
+            (b'3', PieceColor::Black) => (5, 4, 6),
+            _ => return Err(err())
+        };
+
+        let pushed = self.get_piece_at(pawn_y, x)
+            .is_some_and(|p| p.piece_type == PieceType::Pawn && p.color == self.turn.opposite());
+        if !pushed || !self.is_empty(y, x) || !self.is_empty(origin_y, x) {
+            return Err(err());
+        }
+
+        Ok(Position { x, y })
+    }
+
+    // everything that makes the position unreachable from the start position, empty if it's fine
+    pub fn validate(&self) -> Vec<PositionError> {
+        let mut errors = Vec::new();
+        let bb = &self.bb;
+
+        for (color, king) in [(PieceColor::White, bb.white_king), (PieceColor::Black, bb.black_king)] {
+            match king.count_ones() {
+                0 => errors.push(PositionError::MissingKing(color)),
+                1 => {},
+                _ => errors.push(PositionError::TooManyKings(color))
+            }
+        }
+
+        let mut rem = (bb.white_pawns | bb.black_pawns) & (RANK_1 | RANK_8);
+        while rem != 0 {
+            errors.push(PositionError::PawnOnBackRank(Position::from_bitboard(rem)));
+            rem &= rem - 1;
+        }
+
+        let sides = [
+            (PieceColor::White, bb.white_pieces, bb.white_pawns, bb.white_knights, bb.white_bishops, bb.white_rooks, bb.white_queens),
+            (PieceColor::Black, bb.black_pieces, bb.black_pawns, bb.black_knights, bb.black_bishops, bb.black_rooks, bb.black_queens)
+        ];
+
+        for (color, pieces, pawns, knights, bishops, rooks, queens) in sides {
+            let count = pieces.count_ones() as usize;
+            if count > 16 {
+                errors.push(PositionError::TooManyPieces(color, count));
+            }
+
+            let pawn_count = pawns.count_ones() as usize;
+            if pawn_count > 8 {
+                errors.push(PositionError::TooManyPawns(color, pawn_count));
+            }
+
+            // every piece beyond the starting set has to come from a pawn that's no longer on the board
+            let extra = |count: u32, start: u32| count.saturating_sub(start) as usize;
+            let promoted = extra(queens.count_ones(), 1) + extra(rooks.count_ones(), 2) + extra(knights.count_ones(), 2)
+                + extra((bishops & COLOR_MASK).count_ones(), 1) + extra((bishops & !COLOR_MASK).count_ones(), 1);
+
+            if pawn_count <= 8 && promoted > 8 - pawn_count {
+                errors.push(PositionError::ImpossiblePromotions(color));
+            }
+        }
+
+        if bb.white_king.count_ones() == 1 && bb.black_king.count_ones() == 1 {
+            let white = Position::from_bitboard(bb.white_king);
+            let black = Position::from_bitboard(bb.black_king);
+
+            if white.x.abs_diff(black.x) <= 1 && white.y.abs_diff(black.y) <= 1 {
+                errors.push(PositionError::KingsAdjacent);
+            }
+
+            let (control, king) = match self.turn {
+                PieceColor::White => (self.control_bitboards.white_control, bb.black_king),
+                PieceColor::Black => (self.control_bitboards.black_control, bb.white_king)
+            };
+
+            if control & king != 0 {
+                errors.push(PositionError::OpponentInCheck);
+            }
+        }
+
+        for (color, rank) in [(PieceColor::White, 7), (PieceColor::Black, 0)] {
+            let is_piece = |file: usize, piece_type: PieceType| {
+                self.get_piece_at(rank, file).is_some_and(|p| p.piece_type == piece_type && p.color == color)
+            };
+            let king_file = (0..8).find(|&file| is_piece(file, PieceType::King));
+
+            for kingside in [true, false] {
+                if !self.castling.can_castle(color, kingside) {
+                    continue;
+                }
+
+                let rook_file = self.castling.rook_file(color, kingside);
+                let valid = king_file.is_some_and(|king_file| {
+                    let rook_side = if kingside { rook_file > king_file } else { rook_file < king_file };
+                    let standard = king_file == 4 && rook_file == if kingside { 7 } else { 0 };
+
+                    is_piece(rook_file, PieceType::Rook) && rook_side && (self.chess960 || standard)
+                });
+
+                if !valid {
+                    errors.push(PositionError::InvalidCastlingRights(color, kingside));
+                }
+            }
+        }
+
+        if let Some(target) = self.target_square {
+            // the pawn that just moved belongs to the side not to move
+            let squares = match (target.y, self.turn) {
+                (2, PieceColor::White) => Some((3, 1)),
+                (5, PieceColor::Black) => Some((4, 6)),
+                _ => None
+            };
+
+            let valid = squares.is_some_and(|(pawn_y, origin_y)| {
+                self.get_piece_at(pawn_y, target.x).is_some_and(|p| p.piece_type == PieceType::Pawn && p.color == self.turn.opposite())
+                    && self.is_empty(target.y, target.x)
+                    && self.is_empty(origin_y, target.x)
+            });
+
+            if !valid {
+                errors.push(PositionError::InvalidEnPassant(target));
+            }
+        }
+
+        errors
     }
 
     pub fn startpos() -> Board {
         Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
     }
 
+    pub fn to_fen(&self) -> String {
+        let mut position = String::new();
+
+        for rank in 0..8 {
+            let mut empty = 0;
+            for file in 0..8 {
+                if let Some(piece) = self.get_piece_at(rank, file) {
+                    if empty > 0 {
+                        position.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+
+                    let piece_char = match piece.piece_type {
+                        PieceType::Pawn => 'p',
+                        PieceType::Knight => 'n',
+                        PieceType::Bishop => 'b',
+                        PieceType::Rook => 'r',
+                        PieceType::Queen => 'q',
+                        PieceType::King => 'k'
+                    };
+
+                    position.push(if piece.color == PieceColor::White {
+                        piece_char.to_ascii_uppercase()
+                    } else {
+                        piece_char
+                    });
+                } else {
+                    empty += 1;
+                }
+            }
+            if empty > 0 {
+                position.push_str(&empty.to_string());
+            }
+            if rank < 7 {
+                position.push('/');
+            }
+        }
+
+        let turn = if self.turn == PieceColor::White { "w" } else { "b" };
+
+        let mut castling = String::new();
+        for (color, kingside, letter) in [
+            (PieceColor::White, true, 'K'),
+            (PieceColor::White, false, 'Q'),
+            (PieceColor::Black, true, 'k'),
+            (PieceColor::Black, false, 'q')
+        ] {
+            if !self.castling.can_castle(color, kingside) {
+                continue;
+            }
+
+            // X-FEN only names the rook file when another rook stands further out on the same side
+            let rank = if color == PieceColor::White { 7 } else { 0 };
+            let rook_file = self.castling.rook_file(color, kingside);
+            let outer_files = if kingside { (rook_file + 1)..8 } else { 0..rook_file };
+            let outermost = !outer_files.into_iter().any(|file| {
+                self.get_piece_at(rank, file).is_some_and(|p| p.piece_type == PieceType::Rook && p.color == color)
+            });
+
+            if outermost {
+                castling.push(letter);
+            } else {
+                let file = (b'a' + rook_file as u8) as char;
+                castling.push(if color == PieceColor::White { file.to_ascii_uppercase() } else { file });
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let target_square = match self.target_square {
+            Some(t) => format!("{:?}", t),
+            None => "-".to_string()
+        };
+
+        format!("{} {} {} {} {} {}", position, turn, castling, target_square, self.halfmove_clock, self.moves)
+    }
+
     pub fn get_piece_at_bitboard(&self, square: u64) -> Option<BasePiece> {
         if square & self.bb.white_pawns != 0 { return Some((PieceType::Pawn, PieceColor::White)); }
         if square & self.bb.white_knights != 0 { return Some((PieceType::Knight, PieceColor::White)); }
@@ -395,7 +922,7 @@ impl Board {
         None
     }
 
-    pub fn bb_or_pos(&mut self, piece: BasePiece, pos: Position) {
+    fn bb_or_pos(&mut self, piece: BasePiece, pos: Position) {
         let square = pos.to_bitboard();
 
         match piece {
@@ -422,7 +949,7 @@ impl Board {
         self.bb.empty_squares = !self.bb.all_pieces;
     }
 
-    pub fn bb_and_rev_pos(&mut self, piece: BasePiece, pos: Position) {
+    fn bb_and_rev_pos(&mut self, piece: BasePiece, pos: Position) {
         let square = pos.to_bitboard();
         match piece {
             (PieceType::Pawn, PieceColor::White) => self.bb.white_pawns &= !square,
@@ -440,10 +967,10 @@ impl Board {
         }
     }
     
-    pub fn update_bitboard_pos(&mut self, piece: BasePiece, from: Position, to: Position) {
+    fn update_bitboard_pos(&mut self, piece: BasePiece, from: Position, to: Position) {
         
-        self.bb_or_pos(piece, to);
         self.bb_and_rev_pos(piece, from);
+        self.bb_or_pos(piece, to);
         
         let to_bb = to.to_bitboard();
         let from_bb = from.to_bitboard();
@@ -460,7 +987,7 @@ impl Board {
         self.bb.empty_squares = !self.bb.all_pieces;
     }
 
-    pub fn clear(&mut self) {
+    pub fn clear_caches(&mut self) {
         self.moves_cache.clear();
         self.total_moves_cache.clear();
 
@@ -472,37 +999,291 @@ impl Board {
         self.mobility_cache.clear();
     }
 
-    pub fn get_piece(&self, piece_index: usize) -> Option<&Piece> {
-        self.pieces.get(&piece_index)
+    // empties the board, keeping the side to move and the move counters
+    pub fn clear(&mut self) {
+        let castling = Castling::new((false, false), (false, false));
+        let mut board = Board::new(Some(self.moves), Some(self.halfmove_clock), Some(self.turn), Some(castling), None);
+
+        board.chess960 = self.chess960;
+        board.gen_hash();
+
+        *self = board;
     }
 
-    pub fn get_legal_moves(&self, piece_index: usize) -> Vec<Move> {
-        if !self.pieces.contains_key(&piece_index) {
-            return Vec::with_capacity(0);
+    // places a piece outside of play, replacing whatever was on the square, and returns its index
+    pub fn put_piece(&mut self, piece_type: PieceType, color: PieceColor, pos: Position) -> usize {
+        self.remove_piece(pos);
+        self.hash ^= self.en_passant_hash();
+
+        let piece = Piece { piece_type, color, pos, index: self.next_piece_index() };
+
+        self.bb_or_pos(piece.get_base(), pos);
+        self.place_piece(piece);
+        self.hash ^= zobrist::piece_key(piece_type, color, pos);
+
+        self.hash ^= self.en_passant_hash();
+
+        // the new piece may block the en passant target or the pushed pawn's start square
+        if let Some(target) = self.target_square {
+            if self.set_en_passant(Some(target)).is_err() {
+                self.set_en_passant(None).unwrap();
+            }
         }
 
-        let piece = self.pieces.get(&piece_index).unwrap();
-        
-        let mut moves = match piece.piece_type {
-            PieceType::Pawn => get_legal_moves_pawn(&piece, self),
-            PieceType::Knight => get_legal_moves_knight(&piece, self),
-            PieceType::Bishop => get_legal_moves_bishop(&piece, self),
-            PieceType::Rook => get_legal_moves_rook(&piece, self),
-            PieceType::Queen => get_legal_moves_queen(&piece, self),
-            PieceType::King => get_legal_moves_king(&piece, self)
+        self.rebuild_derived();
+
+        piece.index
+    }
+
+    // takes a piece off the board outside of play, dropping castling and en passant rights that relied on it
+    pub fn remove_piece(&mut self, pos: Position) -> Option<Piece> {
+        let index = self.piece_index_at(pos)?;
+
+        if index as i32 == self.target_piece {
+            self.set_en_passant(None).unwrap();
+        }
+
+        match self.get_piece(index).unwrap().piece_type {
+            PieceType::King => self.reset_castling(self.get_piece(index).unwrap().color),
+            PieceType::Rook => self.reset_rook_castling(pos, self.get_piece(index).unwrap().color),
+            _ => {}
+        }
+
+        self.hash ^= self.en_passant_hash();
+
+        self.clear_control(index);
+        let piece = self.take_piece(index).unwrap();
+
+        let square = pos.to_bitboard();
+        self.bb_and_rev_pos(piece.get_base(), pos);
+        if piece.color == PieceColor::White {
+            self.bb.white_pieces &= !square;
+        } else {
+            self.bb.black_pieces &= !square;
+        }
+        self.bb.all_pieces &= !square;
+        self.bb.empty_squares = !self.bb.all_pieces;
+        self.hash ^= zobrist::piece_key(piece.piece_type, piece.color, pos);
+
+        self.hash ^= self.en_passant_hash();
+        self.rebuild_derived();
+
+        Some(piece)
+    }
+
+    pub fn set_turn(&mut self, color: PieceColor) {
+        if self.turn == color {
+            return;
+        }
+
+        // the pushed pawn belongs to whoever moved last, so the target can't outlive a change of turn
+        self.set_en_passant(None).unwrap();
+
+        self.turn = color;
+        self.hash ^= zobrist::turn_key();
+        self.rebuild_derived();
+    }
+
+    // rook_file None takes the right away, otherwise the rook has to be on that file of the home rank
+    pub fn set_castling(&mut self, color: PieceColor, kingside: bool, rook_file: Option<usize>) -> Result<(), PositionError> {
+        if let Some(file) = rook_file {
+            let rank = if color == PieceColor::White { 7 } else { 0 };
+            let is_piece = |file: usize, piece_type: PieceType| {
+                self.get_piece_at(rank, file).is_some_and(|p| p.piece_type == piece_type && p.color == color)
+            };
+
+            let king_file = (0..8).find(|&file| is_piece(file, PieceType::King));
+            let valid = king_file.is_some_and(|king_file| {
+                is_piece(file, PieceType::Rook) && if kingside { file > king_file } else { file < king_file }
+            });
+
+            if !valid {
+                return Err(PositionError::InvalidCastlingRights(color, kingside));
+            }
+        }
+
+        if self.castling.can_castle(color, kingside) {
+            self.hash ^= zobrist::castling_key(color, kingside);
+        }
+
+        self.castling.set(color, kingside, rook_file);
+
+        if rook_file.is_some() {
+            self.hash ^= zobrist::castling_key(color, kingside);
+            self.chess960 |= !self.is_standard_castling();
+        }
+
+        self.rebuild_derived();
+
+        Ok(())
+    }
+
+    // the target has to sit right behind a pawn of the side not to move, with the pawn's start square empty
+    pub fn set_en_passant(&mut self, target: Option<Position>) -> Result<(), PositionError> {
+        let pawn = match target {
+            Some(target) => {
+                let squares = match (target.y, self.turn) {
+                    (2, PieceColor::White) => Some((3, 1)),
+                    (5, PieceColor::Black) => Some((4, 6)),
+                    _ => None
+                };
+
+                let pawn = squares
+                    .filter(|&(_, origin_y)| self.is_empty(target.y, target.x) && self.is_empty(origin_y, target.x))
+                    .and_then(|(pawn_y, _)| self.get_piece_at(pawn_y, target.x))
+                    .filter(|p| p.piece_type == PieceType::Pawn && p.color == self.turn.opposite());
+
+                match pawn {
+                    Some(pawn) => Some(pawn),
+                    None => return Err(PositionError::InvalidEnPassant(target))
+                }
+            },
+            None => None
         };
 
-        for m in &mut moves {
-            if self.would_check(&m) {
-                m.move_type.extend([MoveType::Check]);
+        self.hash ^= self.en_passant_hash();
+
+        self.target_square = target;
+        self.target_piece = pawn.map_or(-1, |p| p.index as i32);
+
+        self.hash ^= self.en_passant_hash();
+        self.rebuild_derived();
+
+        Ok(())
+    }
+
+    // edits can change anything the pieces see, so everything derived from them is rebuilt
+    fn rebuild_derived(&mut self) {
+        self.clear_caches();
+        self.control_bitboards.clear();
+        self.hash_history.clear();
+
+        self.check_control_all();
+    }
+
+    pub fn get_piece(&self, piece_index: usize) -> Option<&Piece> {
+        let square = (*self.piece_list.get(piece_index)?)?;
+        self.mailbox[square].as_ref()
+    }
+
+    pub fn get_piece_mut(&mut self, piece_index: usize) -> Option<&mut Piece> {
+        let square = (*self.piece_list.get(piece_index)?)?;
+        self.mailbox[square].as_mut()
+    }
+
+    // pieces in index order, so iterating the board always visits them the same way
+    pub fn pieces(&self) -> impl Iterator<Item = &Piece> + '_ {
+        self.piece_list.iter().flatten().filter_map(|&square| self.mailbox[square].as_ref())
+    }
+
+    pub fn piece_count(&self) -> usize {
+        self.piece_list.iter().flatten().count()
+    }
+
+    fn next_piece_index(&self) -> usize {
+        self.piece_list.iter().position(|square| square.is_none()).expect("Piece list is full")
+    }
+
+    fn place_piece(&mut self, piece: Piece) {
+        let square = piece.pos.x + piece.pos.y * 8;
+        self.piece_list[piece.index] = Some(square);
+        self.mailbox[square] = Some(piece);
+    }
+
+    fn take_piece(&mut self, piece_index: usize) -> Option<Piece> {
+        let square = self.piece_list[piece_index].take()?;
+        self.mailbox[square].take()
+    }
+
+    fn piece_index_at(&self, pos: Position) -> Option<usize> {
+        self.mailbox[pos.x + pos.y * 8].map(|piece| piece.index)
+    }
+
+    pub fn get_legal_moves(&self, piece_index: usize) -> Vec<Move> {
+        let mut moves = MoveList::new();
+        self.collect_legal_moves(piece_index, &mut moves);
+
+        moves.iter().map(|&m| self.unpack(m)).collect()
+    }
+
+    pub fn collect_legal_moves(&self, piece_index: usize, moves: &mut MoveList) {
+        let start = moves.len();
+
+        if let Some(piece) = self.get_piece(piece_index) {
+            self.push_piece_moves(piece, moves);
+        }
+
+        for m in &mut moves[start..] {
+            if self.gives_check(m.piece_type(), self.get_color_at(m.from()), m.to()) {
+                *m = m.with_check();
             }
         }
+    }
+
+    fn push_piece_moves(&self, piece: &Piece, moves: &mut MoveList) {
+        match piece.piece_type {
+            PieceType::Pawn => get_legal_moves_pawn(piece, self, moves),
+            PieceType::Knight => get_legal_moves_knight(piece, self, moves),
+            PieceType::Bishop => get_legal_moves_bishop(piece, self, moves),
+            PieceType::Rook => get_legal_moves_rook(piece, self, moves),
+            PieceType::Queen => get_legal_moves_queen(piece, self, moves),
+            PieceType::King => get_legal_moves_king(piece, self, moves)
+        }
+    }
+
+    fn get_color_at(&self, pos: Position) -> PieceColor {
+        if self.bb.white_pieces & pos.to_bitboard() != 0 { PieceColor::White } else { PieceColor::Black }
+    }
+
+    // rebuilds the full move from the packed one, the board has to be in the position the move was generated in
+    pub fn unpack(&self, m: PackedMove) -> Move {
+        let from = m.from();
+        let to = m.to();
+        let piece_index = self.piece_index_at(from).expect("No piece on the move's from square");
+        let piece_color = self.get_color_at(from);
+
+        let mut move_type = Vec::with_capacity(3);
+        move_type.push(if m.is_capture() { MoveType::Capture } else if m.is_castling() { MoveType::Castling } else { MoveType::Normal });
+
+        if m.is_promotion() {
+            move_type.push(MoveType::Promotion);
+        } else if m.is_en_passant() {
+            move_type.push(MoveType::EnPassant);
+        }
 
-        moves
+        if m.is_check() {
+            move_type.push(MoveType::Check);
+        }
+
+        let captured = if m.is_en_passant() {
+            self.get_piece_at(from.y, to.x)
+        } else if m.is_capture() {
+            self.get_piece_at(to.y, to.x)
+        } else {
+            None
+        };
+
+        let with = if m.is_castling() {
+            self.get_piece_at(from.y, self.castling.rook_file(piece_color, m.flag() == PackedMove::KING_CASTLE))
+        } else {
+            None
+        };
+
+        Move {
+            from,
+            to,
+            move_type,
+            captured,
+            promote_to: m.promote_to(),
+            piece_index,
+            piece_color,
+            piece_type: m.piece_type(),
+            with
+        }
     }
 
     pub fn get_pins(&self, piece_index: usize) -> Vec<Pin> {
-        if let Some(piece) = self.pieces.get(&piece_index) {
+        if let Some(piece) = self.get_piece(piece_index) {
             match piece.piece_type {
                 PieceType::Bishop => get_pins_bishop(piece, self),
                 PieceType::Rook => get_pins_rook(piece, self),
@@ -525,76 +1306,101 @@ impl Board {
             self.moves += 1;
         }
 
-        for piece in self.pieces.values_mut() {
-            piece.legal_moves_cache.clear();
-        }
         self.result_cache = ResultType::NotCached;
         self.total_moves_cache.clear();
         self.moves_cache.clear();
-        self.hash ^= self.hash_table[12 * 64 + 4];
-        self.hash ^= self.hash_table[12 * 64 + 5];
+        self.hash ^= zobrist::turn_key();
     }
 
     fn reset_rook_castling(&mut self, pos: Position, piece_color: PieceColor) {
-        match piece_color {
-            PieceColor::White => {
-                if pos.x == 0 && self.castling.white.1 {
-                    self.castling.white.1 = false;
-                    self.hash ^= self.hash_table[12 * 64 + 1];
-                } else if pos.x == 7 && self.castling.white.0 {
-                    self.castling.white.0 = false;
-                    self.hash ^= self.hash_table[12 * 64];
-                }
-            },
-            PieceColor::Black => {
-                if pos.x == 0 && self.castling.black.1 {
-                    self.castling.black.1 = false;
-                    self.hash ^= self.hash_table[12 * 64 + 3];
-                } else if pos.x == 7 && self.castling.black.0 {
-                    self.castling.black.0 = false;
-                    self.hash ^= self.hash_table[12 * 64 + 2];
-                }
+        let rank = match piece_color {
+            PieceColor::White => 7,
+            PieceColor::Black => 0
+        };
+
+        if pos.y != rank {
+            return;
+        }
+
+        for kingside in [true, false] {
+            if self.castling.can_castle(piece_color, kingside) && self.castling.rook_file(piece_color, kingside) == pos.x {
+                self.castling.set(piece_color, kingside, None);
+                self.hash ^= zobrist::castling_key(piece_color, kingside);
             }
         }
     }
 
     fn reset_castling(&mut self, piece_color: PieceColor) {
-        match piece_color {
-            PieceColor::White => {
-                self.castling.white = (false, false);
-                self.hash ^= self.hash_table[12 * 64];
-                self.hash ^= self.hash_table[12 * 64 + 1];
-            },
-            PieceColor::Black => {
-                self.castling.black = (false, false);
-                self.hash ^= self.hash_table[12 * 64 + 2];
-                self.hash ^= self.hash_table[12 * 64 + 3];
+        for kingside in [true, false] {
+            if self.castling.can_castle(piece_color, kingside) {
+                self.hash ^= zobrist::castling_key(piece_color, kingside);
             }
         }
+
+        match piece_color {
+            PieceColor::White => self.castling.white = (false, false),
+            PieceColor::Black => self.castling.black = (false, false)
+        }
     }
 
     pub fn make_move(&mut self, m: &Move) -> MoveInfo {
         let history = MoveInfo {
             hash: self.hash,
-            captured_piece: m.captured.clone(),
+            captured_piece: m.captured,
             halfmove_clock: self.halfmove_clock,
             white_check: self.white_check.clone(),
             black_check: self.black_check.clone(),
             turn: self.turn,
             castling: self.castling.clone(),
             promoted_type: if m.move_type.contains(&MoveType::Promotion) {
-                Some(self.pieces.get(&m.piece_index).unwrap().piece_type)
+                Some(self.get_piece(m.piece_index).unwrap().piece_type)
             } else {
                 None
             },
-            control_bitboards: self.control_bitboards.clone(),
             target_square: self.target_square.clone(),
             target_piece: self.target_piece,
-            bitboards: self.bb.clone()
+            bitboards: self.bb.clone(),
+            hash_history: if m.is_irreversible() {
+                std::mem::take(&mut self.hash_history)
+            } else {
+                self.hash_history.push(self.hash);
+                Vec::new()
+            }
         };
 
+        // the old en passant key depends on the pawns around the target, take it out before anything moves
+        self.hash ^= self.en_passant_hash();
+
         let piece_index = m.piece_index;
 
+        let castling_rook = if m.piece_type == PieceType::King && m.move_type.contains(&MoveType::Castling) {
+            m.with
+        } else {
+            None
+        };
+
+        let mut affected = self.control_dependents(self.changed_squares(m, castling_rook.as_ref()));
+        affected.push(piece_index);
+        if let Some(rook) = &castling_rook {
+            affected.push(rook.index);
+        }
+
+        // lift the rook first, in Chess960 the king can land on its square and vice versa
+        if let Some(rook) = &castling_rook {
+            let square = rook.pos.to_bitboard();
+
+            self.bb_and_rev_pos(rook.get_base(), rook.pos);
+            if rook.color == PieceColor::White {
+                self.bb.white_pieces &= !square;
+            } else {
+                self.bb.black_pieces &= !square;
+            }
+            self.bb.all_pieces &= !square;
+            self.bb.empty_squares = !self.bb.all_pieces;
+
+            self.take_piece(rook.index);
+        }
+
         self.update_bitboard_pos((m.piece_type, m.piece_color), m.from, m.to);
 
         self.white_check = CheckInfo::default();
@@ -605,7 +1411,7 @@ impl Board {
 
             self.clear_control(captured.index);
 
-            self.pieces.remove(&captured.index);
+            self.take_piece(captured.index);
             self.bb_and_rev_pos(captured.get_base(), captured.pos);
             if captured.color == PieceColor::White {
                 self.bb.white_pieces &= !captured.pos.to_bitboard();
@@ -618,19 +1424,14 @@ impl Board {
                 self.bb.empty_squares |= captured.pos.to_bitboard();
             }
             
-            let captured_piece_index = captured.to_piece_index();
-            self.hash ^= self.hash_table[captured_piece_index * 64 + captured.pos.y * 8 + captured.pos.x];
-
-            self.board[captured.pos.x][captured.pos.y] = -1;
+            self.hash ^= zobrist::piece_key(captured.piece_type, captured.color, captured.pos);
 
-            if captured.piece_type == PieceType::Rook &&
-                (captured.pos.x == 0 || captured.pos.x == 7) &&
-                (captured.pos.y == if captured.color == PieceColor::White { 7 } else { 0 }) {
+            if captured.piece_type == PieceType::Rook {
                 self.reset_rook_castling(captured.pos, captured.color);
             }
         }
 
-        let piece = self.pieces.get_mut(&piece_index).unwrap();
+        let mut piece = self.take_piece(piece_index).unwrap();
         let pos = piece.pos;
 
         if m.piece_type == PieceType::Pawn && (m.from.y as isize - m.to.y as isize).abs() == 2 {
@@ -644,47 +1445,32 @@ impl Board {
             }
         }
 
-        let hash_index = Piece::piece_index(m.piece_type, m.piece_color);
-
-        self.hash ^= self.hash_table[hash_index * 64 + pos.y * 8 + pos.x];
-        self.hash ^= self.hash_table[hash_index * 64 + m.to.y * 8 + m.to.x];
-
-        self.board[pos.x][pos.y] = -1;
-        self.board[m.to.x][m.to.y] = piece_index as isize;
+        self.hash ^= zobrist::piece_key(m.piece_type, m.piece_color, pos);
+        self.hash ^= zobrist::piece_key(m.piece_type, m.piece_color, m.to);
 
         piece.pos = Position { x: m.to.x, y: m.to.y };
+        self.place_piece(piece);
 
         if m.move_type.contains(&MoveType::Promotion) && m.promote_to.is_some() {
             self.promote_to(piece_index, m.promote_to.unwrap());
         }
 
-        self.check_control_all();
-
-        if m.piece_type == PieceType::King && m.move_type.contains(&MoveType::Castling) && m.with.is_some() {
-            let rook = m.with.clone().unwrap();
-
+        if let Some(rook) = castling_rook {
             let old_rook_pos = rook.pos;
             let new_rook_pos = Position {
                 x: if m.to.x == 2 { 3 } else { 5 },
                 y: m.from.y
             };
 
-            self.update_bitboard_pos(rook.get_base(), old_rook_pos, new_rook_pos);
-
-            self.board[old_rook_pos.x][old_rook_pos.y] = -1;
-            self.board[new_rook_pos.x][new_rook_pos.y] = rook.index as isize;
+            self.bb_or_pos(rook.get_base(), new_rook_pos);
+            self.place_piece(Piece { pos: new_rook_pos, ..rook });
 
-            if let Some(piece) = self.pieces.get_mut(&rook.index) {
-                piece.pos = new_rook_pos.clone();
-            }
-
-            let rook_hash_index = rook.to_piece_index();
-            self.hash ^= self.hash_table[rook_hash_index * 64 + old_rook_pos.y * 8 + old_rook_pos.x];
-            self.hash ^= self.hash_table[rook_hash_index * 64 + new_rook_pos.y * 8 + new_rook_pos.x];
-            
-            self.check_control(rook.index);
+            self.hash ^= zobrist::piece_key(rook.piece_type, rook.color, old_rook_pos);
+            self.hash ^= zobrist::piece_key(rook.piece_type, rook.color, new_rook_pos);
         }
 
+        self.refresh_control(&affected);
+
         if m.piece_type == PieceType::King {
             self.reset_castling(m.piece_color);
         }
@@ -693,78 +1479,194 @@ impl Board {
             self.reset_rook_castling(pos, m.piece_color);
         }
 
-        self.update_board(m.move_type.contains(&MoveType::Capture) || m.move_type.contains(&MoveType::Promotion));
-        self.update_pins();
+        self.update_board(m.is_irreversible());
+        self.hash ^= self.en_passant_hash();
 
         history
     }
 
     pub fn unmake_move(&mut self, m: &Move, history: &MoveInfo) {
-        let current_position = {
-            let piece = self.pieces.get(&m.piece_index).unwrap();
-            piece.pos.clone()
+        let castling_rook = if m.move_type.contains(&MoveType::Castling) {
+            m.with
+        } else {
+            None
         };
 
-        self.board[current_position.x][current_position.y] = -1;
-        self.board[m.from.x][m.from.y] = m.piece_index as isize;
+        let mut affected = self.control_dependents(self.changed_squares(m, castling_rook.as_ref()));
+        affected.push(m.piece_index);
+        if let Some(rook) = &castling_rook {
+            affected.push(rook.index);
+        }
+        if let Some(captured) = &history.captured_piece {
+            affected.push(captured.index);
+        }
+
+        // take both castling pieces off before putting either back, they can swap squares in Chess960
+        let rook = castling_rook.and_then(|rook| self.take_piece(rook.index));
 
-        if let Some(piece) = self.pieces.get_mut(&m.piece_index) {
-            piece.pos = m.from.clone();
+        if let Some(mut piece) = self.take_piece(m.piece_index) {
+            piece.pos = m.from;
             
             if history.promoted_type.is_some() {
                 piece.piece_type = PieceType::Pawn;
             }
-        }
 
-        if let Some(captured) = &history.captured_piece {
-            self.pieces.insert(captured.index, captured.clone());
-            self.board[captured.pos.x][captured.pos.y] = captured.index as isize;
+            self.place_piece(piece);
         }
 
-        if m.move_type.contains(&MoveType::Castling) && m.with.is_some() {
-            let rook = m.with.clone().unwrap();
-            
-            let old_pos = Position {
-                x: if m.to.x < m.from.x { 3 } else { 5 },
-                y: m.from.y
-            };
-
-            let new_pos = Position {
-                x: if m.to.x < m.from.x { 0 } else { 7 },
-                y: m.from.y
-            };
+        if let Some(captured) = history.captured_piece {
+            self.place_piece(captured);
+        }
 
-            self.board[old_pos.x][old_pos.y] = -1;
-            self.board[new_pos.x][new_pos.y] = rook.index as isize;
+        if let Some(rook) = rook {
+            self.place_piece(Piece { pos: castling_rook.unwrap().pos, ..rook });
+        }
 
-            if let Some(piece) = self.pieces.get_mut(&rook.index) {
-                piece.pos = new_pos.clone();
-            }
+        if m.is_irreversible() {
+            self.hash_history = history.hash_history.clone();
+        } else {
+            self.hash_history.pop();
         }
 
         self.hash = history.hash;
         self.halfmove_clock = history.halfmove_clock;
+        if history.turn == PieceColor::Black {
+            self.moves -= 1;
+        }
         self.turn = history.turn;
         self.castling = history.castling.clone();
         self.target_square = history.target_square;
         self.target_piece = history.target_piece;
 
-        self.control_bitboards = history.control_bitboards.clone();
         self.bb = history.bitboards.clone();
         self.total_moves_cache.clear();
 
-        self.check_control_all();
+        self.refresh_control(&affected);
+
+        self.white_check = history.white_check.clone();
+        self.black_check = history.black_check.clone();
+    }
+
+    // passes the turn without moving, for null move pruning and for asking what the other side would
+    // do if it were their move. refused while in check, since the king would be left attacked
+    pub fn make_null_move(&mut self) -> Option<MoveInfo> {
+        if self.get_check(self.turn).checked != 0 {
+            return None;
+        }
+
+        let history = MoveInfo {
+            hash: self.hash,
+            captured_piece: None,
+            halfmove_clock: self.halfmove_clock,
+            white_check: self.white_check.clone(),
+            black_check: self.black_check.clone(),
+            turn: self.turn,
+            castling: self.castling.clone(),
+            promoted_type: None,
+            target_square: self.target_square,
+            target_piece: self.target_piece,
+            bitboards: self.bb.clone(),
+            // repetitions don't count across a null move
+            hash_history: std::mem::take(&mut self.hash_history)
+        };
+
+        // sliders look through the pawn that just double pushed, so they change once it can't be taken anymore
+        let affected = self.en_passant_dependents();
+
+        self.hash ^= self.en_passant_hash();
+        self.target_square = None;
+        self.target_piece = -1;
+
+        self.refresh_control(&affected);
+        self.update_board(false);
+
+        Some(history)
+    }
+
+    pub fn unmake_null_move(&mut self, history: &MoveInfo) {
+        self.hash = history.hash;
+        self.halfmove_clock = history.halfmove_clock;
+        if history.turn == PieceColor::Black {
+            self.moves -= 1;
+        }
+        self.turn = history.turn;
+        self.target_square = history.target_square;
+        self.target_piece = history.target_piece;
+        self.hash_history = history.hash_history.clone();
+
+        self.result_cache = ResultType::NotCached;
+        self.total_moves_cache.clear();
+        self.moves_cache.clear();
+
+        let affected = self.en_passant_dependents();
+        self.refresh_control(&affected);
 
         self.white_check = history.white_check.clone();
         self.black_check = history.black_check.clone();
+    }
+
+    fn en_passant_dependents(&self) -> Vec<usize> {
+        match self.target_piece {
+            -1 => Vec::new(),
+            index => self.get_piece(index as usize)
+                .map_or(Vec::new(), |pawn| self.control_dependents(pawn.pos.to_bitboard()))
+        }
+    }
+
+    // every square whose occupant changes when m is made or unmade
+    fn changed_squares(&self, m: &Move, castling_rook: Option<&Piece>) -> u64 {
+        let mut squares = m.from.to_bitboard() | m.to.to_bitboard();
+
+        if let Some(captured) = m.captured.as_ref().filter(|_| m.move_type.contains(&MoveType::Capture)) {
+            squares |= captured.pos.to_bitboard();
+        }
+
+        if let Some(rook) = castling_rook {
+            squares |= rook.pos.to_bitboard() | Position { x: if m.to.x == 2 { 3 } else { 5 }, y: m.from.y }.to_bitboard();
+        }
+
+        squares
+    }
+
+    // pieces whose control can change when the given squares change occupant: anything that sees one of
+    // them, plus pawns whose pushes run over them
+    fn control_dependents(&self, squares: u64) -> Vec<usize> {
+        let mut affected = Vec::with_capacity(16);
+
+        let mut rem = squares;
+        while rem != 0 {
+            let square = rem & rem.wrapping_neg();
+            if let Some(entries) = self.control_bitboards.control_entries.get(&square) {
+                affected.extend(entries.iter().map(|entry| entry.index));
+            }
+            rem &= rem - 1;
+        }
+
+        let mut rem = (self.bb.white_pawns | self.bb.black_pawns) & ((squares << 8) | (squares >> 8) | (squares << 16) | (squares >> 16));
+        while rem != 0 {
+            let pos = Position::from_bitboard(rem);
+            affected.extend(self.piece_index_at(pos));
+            rem &= rem - 1;
+        }
+
+        affected
+    }
 
-        self.update_pins();
+    fn refresh_control(&mut self, affected: &[usize]) {
+        let mut done: Vec<usize> = Vec::with_capacity(affected.len());
+
+        for &index in affected {
+            if !done.contains(&index) {
+                self.check_control(index);
+                done.push(index);
+            }
+        }
     }
 
     pub fn move_clone(&mut self, m: &Move) -> Board {
         let mut new_board = self.clone();
 
-        new_board.clear();
+        new_board.clear_caches();
 
         new_board.make_move(m);
 
@@ -785,7 +1687,7 @@ impl Board {
     }
 
     pub fn get_controlled_squares(&self, piece_index: usize) -> Vec<Control> {
-        if let Some(piece) = self.pieces.get(&piece_index) {
+        if let Some(piece) = self.get_piece(piece_index) {
             let partial = &piece.to_partial();
             self.get_piece_control(partial)
         } else {
@@ -795,7 +1697,7 @@ impl Board {
 
     pub fn clear_control(&mut self, piece_index: usize) {
         if let Some(positions) = self.control_bitboards.piece_control.remove(&piece_index) {
-            let piece_color = match self.pieces.get(&piece_index) {
+            let piece_color = match self.get_piece(piece_index) {
                 Some(piece) => piece.color,
                 None => return
             };
@@ -808,22 +1710,32 @@ impl Board {
                 let index = rem.trailing_zeros() as usize;
                 let square = 1u64 << index;
                 
+                // other pieces of the same color may still cover the square
+                let (mut control, mut attack, mut defend) = (0u64, 0u64, 0u64);
+
                 if let Some(entries) = self.control_bitboards.control_entries.get_mut(&square) {
                     entries.retain(|entry| entry.index != piece_index);
+
+                    for entry in entries.iter().filter(|entry| entry.color == piece_color) {
+                        control = square;
+                        if entry.control_type == ControlType::Attack { attack = square; }
+                        if entry.control_type == ControlType::Defend { defend = square; }
+                    }
                     
                     if entries.is_empty() {
                         self.control_bitboards.control_entries.remove(&square);
                     }
                 }
 
+                let bitboards = &mut self.control_bitboards;
                 if piece_color == PieceColor::White {
-                    self.control_bitboards.white_control &= !square;
-                    self.control_bitboards.white_attack &= !square;
-                    self.control_bitboards.white_defend &= !square;
+                    bitboards.white_control = (bitboards.white_control & !square) | control;
+                    bitboards.white_attack = (bitboards.white_attack & !square) | attack;
+                    bitboards.white_defend = (bitboards.white_defend & !square) | defend;
                 } else {
-                    self.control_bitboards.black_control &= !square;
-                    self.control_bitboards.black_attack &= !square;
-                    self.control_bitboards.black_defend &= !square;
+                    bitboards.black_control = (bitboards.black_control & !square) | control;
+                    bitboards.black_attack = (bitboards.black_attack & !square) | attack;
+                    bitboards.black_defend = (bitboards.black_defend & !square) | defend;
                 }
 
                 rem &= rem - 1;
@@ -840,14 +1752,14 @@ impl Board {
     }
 
     pub fn check_control(&mut self, piece_index: usize) {
-        if !self.pieces.contains_key(&piece_index) {
+        if self.get_piece(piece_index).is_none() {
             self.clear_control(piece_index);
             return;
         }
 
         self.clear_control(piece_index);
 
-        let piece = self.pieces.get(&piece_index).unwrap();
+        let piece = *self.get_piece(piece_index).unwrap();
         let controlled_squares = self.get_controlled_squares(piece_index);
 
         let mut lookup_entries = Vec::with_capacity(controlled_squares.len());
@@ -873,7 +1785,9 @@ impl Board {
                 } else {
                     &mut self.black_check
                 };
-                if check_info.checked != 0 && check_info.checked != control.pos.to_bitboard() {
+                // the checker's own square is always part of its block positions, so a second checker is one that isn't
+                let other_checker = check_info.block_positions.as_ref().is_some_and(|positions| !positions.contains(&piece.pos));
+                if check_info.checked != 0 && other_checker {
                     check_info.double_checked |= control.pos.to_bitboard();
                 } else {
                     if piece.piece_type.is_directional() {
@@ -912,18 +1826,18 @@ impl Board {
                 }
             }
 
-            self.control_bitboards.control_entries
-                .entry(pos)
-                .or_insert(Vec::new())
-                .push(ControlTableEntry {
-                    index: piece_index,
-                    control_type: control.control_type,
-                    color: piece.color,
-                    obscured: control.obscured,
-                    is_king: piece.piece_type == PieceType::King,
-                    origin: piece.to_partial(),
-                    threat: control.threat
-                });
+            // kept in piece index order so the result doesn't depend on the order pieces were refreshed in
+            let entries = self.control_bitboards.control_entries.entry(pos).or_default();
+            let at = entries.partition_point(|entry| entry.index < piece_index);
+            entries.insert(at, ControlTableEntry {
+                index: piece_index,
+                control_type: control.control_type,
+                color: piece.color,
+                obscured: control.obscured,
+                is_king: piece.piece_type == PieceType::King,
+                origin: piece.to_partial(),
+                threat: control.threat
+            });
 
             lookup_entries.push((control.pos, control.control_type));
         }
@@ -936,22 +1850,20 @@ impl Board {
     }
 
     pub fn check_control_all(&mut self) {
-        let pieces: Vec<usize> = self.pieces.keys().cloned().collect();
+        let pieces: Vec<usize> = self.pieces().map(|piece| piece.index).collect();
         for piece in pieces {
             self.check_control(piece);
         }
     }
 
     pub fn promote_to(&mut self, piece_index: usize, piece_type: PieceType) {
-        let piece = self.pieces.get_mut(&piece_index).unwrap();
-        
-        self.hash ^= self.hash_table[piece.to_piece_index() * 64 + piece.pos.y * 8 + piece.pos.x];
+        let pawn = *self.get_piece(piece_index).unwrap();
+        let piece = Piece { piece_type, ..pawn };
         
-        piece.piece_type = piece_type;
+        self.hash ^= zobrist::piece_key(pawn.piece_type, pawn.color, pawn.pos);
+        self.hash ^= zobrist::piece_key(piece_type, piece.color, piece.pos);
 
-        self.hash ^= self.hash_table[piece.to_piece_index() * 64 + piece.pos.y * 8 + piece.pos.x];
-
-        let piece = self.pieces.get(&piece_index).unwrap().clone();
+        self.get_piece_mut(piece_index).unwrap().piece_type = piece_type;
         
         self.bb_and_rev_pos((PieceType::Pawn, piece.color), piece.pos);
         self.bb_or_pos(piece.get_base(), piece.pos);
@@ -975,66 +1887,102 @@ impl Board {
         self.get_piece_at(pos.y, pos.x)
     }
 
-    pub fn get_result(&mut self) -> ResultType {
+    pub fn is_repetition(&self, count: usize) -> bool {
+        let mut occurrences = 1;
+
+        // the stack is cleared on irreversible moves, and only positions with the same side to move can match
+        for &hash in self.hash_history.iter().rev().skip(1).step_by(2) {
+            if hash == self.hash {
+                occurrences += 1;
+                if occurrences >= count {
+                    return true;
+                }
+            }
+        }
+
+        occurrences >= count
+    }
+
+    pub fn get_result(&self) -> ResultType {
         let check = self.get_check(self.turn);
         let king_index = self.get_king(self.turn).expect(&format!("Expected both kings\n{:?}\n{:?}", self, self.black_check)).index;
-        if (check.double_checked != 0u64 || (check.checked != 0u64 && self.get_block_moves(self.turn).is_empty())) && self.get_legal_moves(king_index).is_empty() {
+        let in_check = check.checked != 0u64 || check.double_checked != 0u64;
+        let mut moves = MoveList::new();
+        if check.checked != 0u64 && check.double_checked == 0u64 {
+            self.collect_block_moves(self.turn, &mut moves);
+        }
+        if let Some(king) = self.get_piece(king_index) {
+            self.push_piece_moves(king, &mut moves);
+        }
+        if in_check && moves.is_empty() {
             match self.turn {
                 PieceColor::White => ResultType::BlackCheckmate,
                 PieceColor::Black => ResultType::WhiteCheckmate
             }
+        } else if !in_check && !self.has_legal_moves(self.turn) {
+            ResultType::Stalemate
+        } else if self.is_insufficient_material() {
+            ResultType::Draw(DrawReason::InsufficientMaterial)
+        } else if self.halfmove_clock >= 150 {
+            ResultType::Draw(DrawReason::SeventyFiveMoves)
+        } else if self.is_repetition(5) {
+            ResultType::Draw(DrawReason::FivefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            ResultType::Claimable(DrawReason::FiftyMoves)
+        } else if self.is_repetition(3) {
+            ResultType::Claimable(DrawReason::ThreefoldRepetition)
         } else {
-            let no_material = (self.bb.white_queens | self.bb.white_rooks | self.bb.white_pawns | self.bb.black_queens | self.bb.black_rooks | self.bb.black_pawns).count_ones() == 0;
-            let white_no_minor = (self.bb.white_knights | self.bb.white_bishops).count_ones() == 0;
-            let black_no_minor = (self.bb.black_knights | self.bb.black_bishops).count_ones() == 0;
-            let white_one_bishop = self.bb.white_bishops.count_ones() == 1 && self.bb.white_knights.count_ones() == 0;
-            let black_one_bishop = self.bb.black_bishops.count_ones() == 1 && self.bb.black_knights.count_ones() == 0;
-            let white_one_knight = self.bb.white_knights.count_ones() == 1 && self.bb.white_bishops.count_ones() == 0;
-            let black_one_knight = self.bb.black_knights.count_ones() == 1 && self.bb.black_bishops.count_ones() == 0;
-            if self.halfmove_clock > 100 ||
-                (no_material && white_no_minor && black_no_minor) ||
-                (no_material && white_no_minor && black_one_bishop) ||
-                (no_material && black_no_minor && white_one_bishop) ||
-                (no_material && white_no_minor && black_one_knight) ||
-                (no_material && black_no_minor && white_one_knight) ||
-                (no_material && white_one_bishop && black_one_bishop && self.bb.white_bishops & COLOR_MASK == self.bb.black_bishops & COLOR_MASK) {
-                ResultType::Draw
-            } else {
-                ResultType::None
-            }
+            ResultType::None
         }
     }
 
-    fn collect_all_legal_moves(&self, color: PieceColor, moves: &mut Vec<Move>, quiescence: bool) {
-        if moves.is_empty() {
-            moves.reserve(MOVE_PREALLOC);
-        }
+    pub fn has_legal_moves(&self, color: PieceColor) -> bool {
+        let mut moves = MoveList::new();
 
-        let piece_indices: HashSet<usize> = self.pieces.iter()
-            .filter_map(|(&index, piece)| {
-                if piece.color == color {
-                    Some(index)
-                } else {
-                    None
-                }
-            })
-            .collect::<HashSet<usize>>();
-
-        for &index in &piece_indices {
-            let piece_moves = self.get_legal_moves(index);
-            if quiescence {
-                if !piece_moves.is_empty() {
-                    moves.extend(piece_moves.iter().filter(|m| m.move_type.contains(&MoveType::Capture) || m.move_type.contains(&MoveType::Promotion)).cloned());
-                }
-            } else {
-                if !piece_moves.is_empty() {
-                    moves.extend(piece_moves);
-                }
+        self.pieces().any(|piece| {
+            if piece.color == color {
+                self.push_piece_moves(piece, &mut moves);
             }
+            !moves.is_empty()
+        })
+    }
+
+    pub fn is_insufficient_material(&self) -> bool {
+        let no_material = (self.bb.white_queens | self.bb.white_rooks | self.bb.white_pawns | self.bb.black_queens | self.bb.black_rooks | self.bb.black_pawns).count_ones() == 0;
+        let white_no_minor = (self.bb.white_knights | self.bb.white_bishops).count_ones() == 0;
+        let black_no_minor = (self.bb.black_knights | self.bb.black_bishops).count_ones() == 0;
+        let white_one_bishop = self.bb.white_bishops.count_ones() == 1 && self.bb.white_knights.count_ones() == 0;
+        let black_one_bishop = self.bb.black_bishops.count_ones() == 1 && self.bb.black_knights.count_ones() == 0;
+        let white_one_knight = self.bb.white_knights.count_ones() == 1 && self.bb.white_bishops.count_ones() == 0;
+        let black_one_knight = self.bb.black_knights.count_ones() == 1 && self.bb.black_bishops.count_ones() == 0;
+        let white_one_minor = white_no_minor || white_one_bishop || white_one_knight;
+        let black_one_minor = black_no_minor || black_one_bishop || black_one_knight;
+        let same_color_bishops = (self.bb.white_bishops & COLOR_MASK != 0) == (self.bb.black_bishops & COLOR_MASK != 0);
+
+        no_material && (
+            (white_no_minor && black_one_minor) ||
+            (black_no_minor && white_one_minor) ||
+            (white_one_bishop && black_one_bishop && same_color_bishops)
+        )
+    }
+
+    fn collect_all_legal_moves(&self, color: PieceColor, moves: &mut MoveList) {
+        let piece_indices = self.pieces()
+            .filter(|piece| piece.color == color)
+            .map(|piece| piece.index);
+
+        for index in piece_indices {
+            self.collect_legal_moves(index, moves);
         }
     }
 
     pub fn get_total_legal_moves_quiescence(&mut self, _color: Option<PieceColor>, quiescence: bool) -> Vec<Move> {
+        let moves = self.generate_moves(_color, quiescence);
+
+        moves.iter().map(|&m| self.unpack(m)).collect()
+    }
+
+    pub fn generate_moves(&mut self, _color: Option<PieceColor>, quiescence: bool) -> MoveList {
         let color = _color.unwrap_or(self.turn);
 
         if let Some(cached) = self.total_moves_cache.get(&color) {
@@ -1043,28 +1991,47 @@ impl Board {
             }
         }
 
-        let mut result = Vec::with_capacity(MOVE_PREALLOC);
+        let mut result = MoveList::new();
+        self.generate_move_list(color, quiescence, &mut result);
+
+        self.total_moves_cache.insert(color, result.clone());
+
+        result
+    }
+
+    pub fn generate_legal_moves(&self, color: PieceColor, quiescence: bool) -> Vec<Move> {
+        let mut moves = MoveList::new();
+        self.generate_move_list(color, quiescence, &mut moves);
 
+        moves.iter().map(|&m| self.unpack(m)).collect()
+    }
+
+    pub fn generate_move_list(&self, color: PieceColor, quiescence: bool, moves: &mut MoveList) {
         let info = self.get_check(color);
         if info.double_checked != 0u64 {
             if let Some(king) = self.get_king(color) {
-                result = self.get_legal_moves(king.index);
+                self.collect_legal_moves(king.index, moves);
             }
         } else if info.checked != 0u64 {
             if let Some(king) = self.get_king(color) {
-                result = self.get_legal_moves(king.index);
-
-                let block_moves = self.get_block_moves(color);
-                result.reserve(block_moves.len());
-                result.extend(block_moves)
+                self.collect_legal_moves(king.index, moves);
+                self.collect_block_moves(color, moves);
+            }
+        } else if quiescence {
+            let start = moves.len();
+            movegen::generate_captures(self, color, moves);
+
+            let mut kept = start;
+            for i in start..moves.len() {
+                if self.is_legal(moves[i]) {
+                    moves[kept] = moves[i];
+                    kept += 1;
+                }
             }
+            moves.truncate(kept);
         } else {
-            self.collect_all_legal_moves(color, &mut result, quiescence);
+            self.collect_all_legal_moves(color, moves);
         }
-
-        self.total_moves_cache.insert(color, result.clone());
-
-        result
     }
 
     pub fn get_total_legal_moves(&mut self, _color: Option<PieceColor>) -> Vec<Move> {
@@ -1072,9 +2039,17 @@ impl Board {
     }
 
     pub fn get_block_moves(&self, color: PieceColor) -> Vec<Move> {
-        let block_positions = self.get_check(color).block_positions.clone().unwrap_or(Vec::with_capacity(0));
-        let mut moves = vec![];
-        for pos in block_positions {
+        let mut moves = MoveList::new();
+        self.collect_block_moves(color, &mut moves);
+
+        moves.iter().map(|&m| self.unpack(m)).collect()
+    }
+
+    pub fn collect_block_moves(&self, color: PieceColor, moves: &mut MoveList) {
+        let Some(block_positions) = self.get_check(color).block_positions.as_ref() else {
+            return;
+        };
+        for &pos in block_positions {
             let has_enemy_piece = if let Some(piece) = self.get_piece_at(pos.y, pos.x) {
                 piece.color != color
             } else {
@@ -1125,20 +2100,32 @@ impl Board {
                         }
                     }
                 );
-            moves.extend(control.map(|c| c.to_move(self, pos, is_en_passant && (en_passant_positions & c.origin.pos.to_bitboard() != 0))))
+            for c in control {
+                let start = moves.len();
+                c.push_moves(self, pos, is_en_passant && (en_passant_positions & c.origin.pos.to_bitboard() != 0), moves);
+
+                for m in &mut moves[start..] {
+                    if self.gives_check(m.piece_type(), color, m.to()) {
+                        *m = m.with_check();
+                    }
+                }
+            }
         }
-        moves
     }
 
     pub fn would_check(&self, m: &Move) -> bool {
+        self.gives_check(m.piece_type, m.piece_color, m.to)
+    }
+
+    fn gives_check(&self, piece_type: PieceType, color: PieceColor, to: Position) -> bool {
         let partial = PartialPiece {
-            piece_type: m.piece_type,
-            pos: m.to,
-            color: m.piece_color
+            piece_type,
+            pos: to,
+            color
         };
         let controlled_squares = self.get_piece_control(&partial);
         
-        let king_pos = self.get_king_pos(m.piece_color.opposite());
+        let king_pos = self.get_king_pos(color.opposite());
         for control in &controlled_squares {
             if control.pos == king_pos {
                 return true
@@ -1147,13 +2134,183 @@ impl Board {
         false
     }
 
-    pub fn get_piece_at(&self, rank: usize, file: usize) -> Option<Piece> {
-        if !Board::in_bounds(rank, file) { return None; }
-        if self.board[file][rank] > -1 {
-            self.pieces.get(&(self.board[file][rank] as usize)).cloned()
+    // whether a pseudo-legal move keeps the mover's king out of check. castling is checked in full
+    // when it's generated, so it only has to still be available
+    pub fn is_legal(&self, m: PackedMove) -> bool {
+        let from = m.from();
+        let to = m.to();
+        let color = self.get_color_at(from);
+
+        if m.is_castling() {
+            let Some(king) = self.get_king(color) else { return false };
+            let kingside = m.flag() == PackedMove::KING_CASTLE;
+
+            return self.castling.can_castle(color, kingside) && get_castling_move(&king, self, kingside).is_some_and(|c| c.to() == to);
+        }
+
+        let mut occupied = (self.bb.all_pieces & !from.to_bitboard()) | to.to_bitboard();
+        let mut enemy = self.bb.pieces_of_color(color.opposite()) & !to.to_bitboard();
+
+        if m.is_en_passant() {
+            let captured = Position { x: to.x, y: from.y }.to_bitboard();
+            occupied &= !captured;
+            enemy &= !captured;
+        }
+
+        let king = if m.piece_type() == PieceType::King {
+            to
+        } else if self.bb.pieces_of(color)[PieceType::King.index()] != 0 {
+            self.get_king_pos(color)
         } else {
-            None
+            return true;
+        };
+
+        self.attackers_to(king, occupied) & enemy == 0
+    }
+
+    // pieces of both colors attacking the square. sliders are looked up through `occupied`, so
+    // taking a piece out of it uncovers whatever was x-raying through that piece
+    pub fn attackers_to(&self, pos: Position, occupied: u64) -> u64 {
+        let square = pos.x + pos.y * 8;
+        let bb = &self.bb;
+
+        let diagonal = bb.white_bishops | bb.black_bishops | bb.white_queens | bb.black_queens;
+        let straight = bb.white_rooks | bb.black_rooks | bb.white_queens | bb.black_queens;
+
+        let attackers = (attacks::pawn(square, PieceColor::Black) & bb.white_pawns) |
+                        (attacks::pawn(square, PieceColor::White) & bb.black_pawns) |
+                        (attacks::knight(square) & (bb.white_knights | bb.black_knights)) |
+                        (attacks::king(square) & (bb.white_king | bb.black_king)) |
+                        (attacks::bishop(square, occupied) & diagonal) |
+                        (attacks::rook(square, occupied) & straight);
+
+        attackers & occupied
+    }
+
+    fn least_valuable_attacker(&self, attackers: u64, color: PieceColor) -> Option<(PieceType, u64)> {
+        self.bb.pieces_of(color).iter()
+            .zip(PIECE_TYPES)
+            .map(|(set, piece_type)| (piece_type, set & attackers))
+            .find(|(_, set)| *set != 0)
+            .map(|(piece_type, set)| (piece_type, set & set.wrapping_neg()))
+    }
+
+    // what the move wins by itself, the value of the piece it leaves on the target square and the
+    // occupancy once it's made. castling never exchanges anything
+    fn exchange_start(&self, m: &Move) -> Option<(i32, i32, u64)> {
+        if m.move_type.contains(&MoveType::Castling) {
+            return None;
+        }
+
+        let mut occupied = self.bb.all_pieces & !m.from.to_bitboard();
+        let mut gain = 0;
+        let mut on_square = see_value(m.piece_type);
+
+        // en passant takes a pawn that isn't on the target square
+        if let Some(captured) = &m.captured {
+            occupied &= !captured.pos.to_bitboard();
+            gain += see_value(captured.piece_type);
+        }
+
+        if let Some(promote_to) = m.promote_to {
+            gain += see_value(promote_to) - see_value(PieceType::Pawn);
+            on_square = see_value(promote_to);
+        }
+
+        Some((gain, on_square, occupied))
+    }
+
+    // static exchange evaluation: the material the mover comes out with when both sides keep
+    // recapturing on the target square with their least valuable attacker, each free to stop.
+    // pins are ignored and a king only recaptures when nothing can take it back
+    pub fn see(&self, m: &Move) -> i32 {
+        let Some((gain, mut on_square, mut occupied)) = self.exchange_start(m) else {
+            return 0;
+        };
+
+        let mut gains = [0i32; 64];
+        let mut depth = 0;
+        gains[0] = gain;
+
+        let mut color = m.piece_color.opposite();
+        loop {
+            let attackers = self.attackers_to(m.to, occupied);
+            let Some((piece_type, from)) = self.least_valuable_attacker(attackers, color) else {
+                break;
+            };
+
+            if piece_type == PieceType::King {
+                let defenders = self.attackers_to(m.to, occupied ^ from);
+                if self.least_valuable_attacker(defenders, color.opposite()).is_some() {
+                    break;
+                }
+            }
+
+            depth += 1;
+            gains[depth] = on_square - gains[depth - 1];
+            on_square = see_value(piece_type);
+            occupied ^= from;
+            color = color.opposite();
+        }
+
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+
+        gains[0]
+    }
+
+    // same as see(m) >= threshold, but stops as soon as the outcome is decided
+    pub fn see_ge(&self, m: &Move, threshold: i32) -> bool {
+        let Some((gain, on_square, mut occupied)) = self.exchange_start(m) else {
+            return threshold <= 0;
+        };
+
+        // what the side that just captured can afford to lose and still reach the threshold
+        let mut swap = gain - threshold;
+        if swap < 0 {
+            return false;
+        }
+
+        swap = on_square - swap;
+        if swap <= 0 {
+            return true;
+        }
+
+        let mut color = m.piece_color;
+        let mut result = true;
+        loop {
+            color = color.opposite();
+
+            let attackers = self.attackers_to(m.to, occupied);
+            let Some((piece_type, from)) = self.least_valuable_attacker(attackers, color) else {
+                break;
+            };
+
+            result = !result;
+
+            if piece_type == PieceType::King {
+                let defenders = self.attackers_to(m.to, occupied ^ from);
+                let defended = self.least_valuable_attacker(defenders, color.opposite()).is_some();
+
+                return if defended { !result } else { result };
+            }
+
+            swap = see_value(piece_type) - swap;
+            if swap < result as i32 {
+                break;
+            }
+
+            occupied ^= from;
         }
+
+        result
+    }
+
+    pub fn get_piece_at(&self, rank: usize, file: usize) -> Option<Piece> {
+        if !Board::in_bounds(rank, file) { return None; }
+        self.mailbox[file + rank * 8]
     }
 
     pub fn square_free(&self, rank: usize, file: usize, color: PieceColor) -> bool {
@@ -1185,54 +2342,93 @@ impl Board {
     }
 
     pub fn is_empty(&self, rank: usize, file: usize) -> bool {
-        self.board[file][rank] == -1
+        !Board::in_bounds(rank, file) || self.mailbox[file + rank * 8].is_none()
     }
 
     pub fn is_pinned(&self, rank: usize, file: usize) -> Option<Vector> {
         if !Board::in_bounds(rank, file) { return None };
         if self.is_empty(rank, file) { return None };
-        let pos = Position {
-            x: file,
-            y: rank
-        };
-        let pins = &self.pin_table[rank][file];
-        let mut dir = None;
-        for pin in pins {
-            if pin.dir.in_direction(pos, pin.position) && !pin.is_phantom {
-                dir = Some(pin.dir);
-            }
-        }
-        return dir;
+
+        self.find_pin(Position { x: file, y: rank })
+            .filter(|pin| !pin.is_phantom)
+            .map(|pin| pin.dir)
     }
 
     pub fn is_phantom_pinned(&self, rank: usize, file: usize) -> bool {
         if !Board::in_bounds(rank, file) { return false };
         if self.is_empty(rank, file) { return false };
-        let pos = Position {
-            x: file,
-            y: rank
-        };
-        let pins = &self.pin_table[rank][file];
-        pins.iter().any(|p| p.dir.in_direction(pos, p.position) && p.is_phantom)
+
+        self.find_pin(Position { x: file, y: rank }).is_some_and(|pin| pin.is_phantom)
     }
 
-    pub fn update_pins(&mut self) {
-        self.pin_table = vec![vec![vec![]; 8]; 8];
+    // pins are read off the board on demand: the line from the piece to its own king has to be clear,
+    // and the first piece on the other side has to be an enemy slider moving along that line. the pawn
+    // that just made a double push is looked through, which makes the pin a phantom one (en passant)
+    pub fn find_pin(&self, pos: Position) -> Option<Pin> {
+        let (_, color) = self.get_piece_at_bitboard(pos.to_bitboard())?;
+        let enemy = color.opposite();
+
+        let king = if color == PieceColor::White { self.bb.white_king } else { self.bb.black_king };
+        if king == 0 {
+            return None;
+        }
+        let king_pos = Position::from_bitboard(king);
+
+        let dx = king_pos.x as isize - pos.x as isize;
+        let dy = king_pos.y as isize - pos.y as isize;
+        if (dx == 0 && dy == 0) || (dx != 0 && dy != 0 && dx.abs() != dy.abs()) {
+            return None;
+        }
+
+        let dir = Vector { x: dx.signum(), y: dy.signum() };
+        let diagonal = dir.x != 0 && dir.y != 0;
+        let mut is_phantom = false;
 
-        for (&index, piece) in &self.pieces {
-            if piece.piece_type.is_directional() {
-                let pins = self.get_pins(index);
-                for pin in pins {
-                    self.pin_table[pin.position.y][pin.position.x].push(pin);
+        let mut square = pos.shift(dir);
+        while square != king_pos {
+            if !self.is_empty(square.y, square.x) {
+                if !self.is_target_piece(square, enemy) {
+                    return None;
                 }
+                is_phantom = true;
             }
+            square = square.shift(dir);
         }
+
+        let back = dir.inv();
+        let mut square = pos.shift(back);
+        while Board::in_bounds(square.y, square.x) {
+            if let Some((piece_type, piece_color)) = self.get_piece_at_bitboard(square.to_bitboard()) {
+                if self.is_target_piece(square, enemy) {
+                    is_phantom = true;
+                } else {
+                    let slider = piece_type == PieceType::Queen || piece_type == if diagonal { PieceType::Bishop } else { PieceType::Rook };
+
+                    return (piece_color == enemy && slider).then_some(Pin {
+                        position: pos,
+                        to: king_pos,
+                        color: enemy,
+                        dir,
+                        is_phantom
+                    });
+                }
+            }
+            square = square.shift(back);
+        }
+
+        None
+    }
+
+    fn is_target_piece(&self, pos: Position, color: PieceColor) -> bool {
+        self.target_piece > -1
+            && self.piece_index_at(pos) == Some(self.target_piece as usize)
+            && self.get_piece_at_bitboard(pos.to_bitboard()).is_some_and(|(_, c)| c == color)
     }
 
     pub fn calculate_phase(&self) -> f64 {
         let mut phase = MAX_PHASE;
 
-        for piece in self.pieces.values() {
+        for piece in self.pieces() {
             if piece.piece_type == PieceType::King {
                 continue;
             }
@@ -1252,83 +2448,83 @@ impl Board {
     }
 
     pub fn gen_hash(&mut self) {
-        let mut hash_array = Vec::with_capacity(782);
-        let mut hash = i64::MAX;
+        self.hash = self.polyglot_key();
+    }
 
-        let mut rng = StdRng::seed_from_u64(9009);
+    // computed from scratch, self.hash is kept equal to it incrementally
+    pub fn polyglot_key(&self) -> u64 {
+        let mut key = 0u64;
 
-        for _ in 0..((64 * 12) + 4 + 2 + 8) {
-            hash_array.push(rng.random::<i64>());
+        for piece in self.pieces() {
+            key ^= zobrist::piece_key(piece.piece_type, piece.color, piece.pos);
         }
 
-        for piece in self.pieces.values() {
-            let pos = piece.pos;
-            let piece_index = piece.to_piece_index();
-
-            hash ^= hash_array[piece_index * 64 + pos.y * 8 + pos.x];
+        for color in [PieceColor::White, PieceColor::Black] {
+            for kingside in [true, false] {
+                if self.castling.can_castle(color, kingside) {
+                    key ^= zobrist::castling_key(color, kingside);
+                }
+            }
         }
 
-        if self.castling.white.0 { hash ^= hash_array[12 * 64]; }
-        if self.castling.white.1 { hash ^= hash_array[12 * 64 + 1]; }
-        if self.castling.black.0 { hash ^= hash_array[12 * 64 + 2]; }
-        if self.castling.black.1 { hash ^= hash_array[12 * 64 + 3]; }
+        key ^= self.en_passant_hash();
 
         if self.turn == PieceColor::White {
-            hash ^= hash_array[12 * 64 + 4];
-        } else {
-            hash ^= hash_array[12 * 64 + 5];
+            key ^= zobrist::turn_key();
         }
 
-        if let Some(t) = &self.target_square {
-            hash ^= hash_array[12 * 64 + 4 + 2 + t.y];
-        }
+        key
+    }
 
-        self.hash = hash;
-        self.hash_table = hash_array;
+    // polyglot only hashes the en passant file when a pawn of the side to move stands next to the pushed pawn
+    fn en_passant_hash(&self) -> u64 {
+        let Some(target) = self.target_square else { return 0 };
+
+        let (pawns, rank) = if self.turn == PieceColor::White {
+            (self.bb.white_pawns, 3)
+        } else {
+            (self.bb.black_pawns, 4)
+        };
+
+        let mut adjacent = 0u64;
+        if target.x > 0 { adjacent |= 1u64 << (target.x - 1 + rank * 8); }
+        if target.x < 7 { adjacent |= 1u64 << (target.x + 1 + rank * 8); }
+
+        if pawns & adjacent != 0 {
+            zobrist::en_passant_key(target.x)
+        } else {
+            0
+        }
     }
 }
 
+fn see_value(piece_type: PieceType) -> i32 {
+    PIECE_VALUES[piece_type.index()] as i32
+}
+
 impl fmt::Debug for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "  ")?;
-        for i in 0..8 {
-            write!(f, "{} ", "abcdefgh".chars().nth(i).unwrap())?;
-        }
-        write!(f, "\n")?;
-        for rank in 0..8 {
-            write!(f, "{} ", 8 - rank)?;
-            for file in 0..8 {
-                let piece = self.board[file][rank];
-                if piece == -1 {
-                    write!(f, ". ")?;
-                } else {
-                    let piece = self.pieces.get(&(piece as usize)).unwrap();
+        write!(f, "{}", render::ascii(self))
+    }
+}
 
-                    let piece_char = match piece.piece_type {
-                        PieceType::Pawn => "p",
-                        PieceType::Knight => "n",
-                        PieceType::Bishop => "b",
-                        PieceType::Rook => "r",
-                        PieceType::Queen => "q",
-                        PieceType::King => "k"
-                    };
+// boards are written as FEN, the repetition history does not survive the round trip
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
 
-                    write!(f, "{} ", if piece.color == PieceColor::White {
-                        piece_char.to_uppercase()
-                    } else {
-                        piece_char.to_owned()
-                    })?;
-                }
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fen = String::deserialize(deserializer)?;
+        Board::parse_fen(&fen, false).map_err(de::Error::custom)
     }
 }
 
 #[test]
 fn result_check() {
-    let mut black_checkmate = Board::from_fen("2k5/1ppp4/pn5B/8/8/8/1Q3PPP/4r1K1 w - - 0 1");
+    let black_checkmate = Board::from_fen("2k5/1ppp4/pn5B/8/8/8/1Q3PPP/4r1K1 w - - 0 1");
 
     assert!(black_checkmate.get_result() == ResultType::BlackCheckmate);
 }
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{self, BufRead};
+use std::fs;
+use std::io;
 use std::path::Path;
 
 use crate::board::Board;
 use crate::moves::Move;
+use crate::pgn::PgnReader;
+
+const SAN_SUFFIXES: [char; 4] = ['+', '#', '!', '?'];
 
 #[derive(Debug, Clone)]
 pub struct OpeningBook {
@@ -70,75 +73,32 @@ impl OpeningBook {
     }
 
     pub fn load_pgn_file<P: AsRef<Path>>(&mut self, file_path: P) -> io::Result<usize> {
-        let file = File::open(file_path)?;
-        let reader = io::BufReader::new(file);
-        
-        let mut in_game = false;
-        let mut moves = Vec::new();
+        let text = fs::read_to_string(&file_path)?;
         let mut loaded_games = 0;
-        
-        for line in reader.lines() {
-            let line = line?;
-            let trimmed = line.trim();
-            
-            if trimmed.is_empty() {
-                continue;
-            }
-            
-            if trimmed.starts_with('[') {
-                if in_game && !moves.is_empty() {
-                    self.add_game(&moves);
-                    moves.clear();
-                    loaded_games += 1;
-                }
-                in_game = false;
-                continue;
-            }
-            
-            in_game = true;
-            
-            for token in trimmed.split_whitespace() {
-                if token.contains('.') && !token.starts_with('.') {
-                    let parts: Vec<&str> = token.split('.').collect();
-                    if parts.len() > 1 && !parts[1].is_empty() {
-                        let m = parts[1];
-                        self.process_token(m, &mut moves)?;
+
+        for game in PgnReader::new(&text) {
+            match game {
+                Ok(game) => {
+                    let moves: Vec<String> = game.moves.iter().map(|m| OpeningBook::book_key(&m.san)).collect();
+                    if !moves.is_empty() {
+                        self.add_game(&moves);
+                        loaded_games += 1;
                     }
-                } else {
-                    self.process_token(token, &mut moves)?;
-                }
+                },
+                Err(e) => eprintln!("Skipping game in {:?}: {}", file_path.as_ref(), e)
             }
         }
-        
-        if !moves.is_empty() {
-            self.add_game(&moves);
-            loaded_games += 1;
-        }
-        
+
         Ok(loaded_games)
     }
-    
-    fn process_token(&mut self, token: &str, moves: &mut Vec<String>) -> io::Result<()> {
-        if token.parse::<u32>().is_ok() {
-            return Ok(());
-        }
-        
-        if token == "1-0" || token == "0-1" || token == "1/2-1/2" || token == "*" {
-            return Ok(());
-        }
-        
-        let m = token.trim_end_matches(&['+', '#', '!', '?'][..])
-            .split(';').next().unwrap()
-            .split('{').next().unwrap()
-            .to_string();
-        
-        if m.is_empty() || m == "0-0" || m == "0-0-0" {
-            return Ok(());
-        }
 
-        moves.push(m);
-        
-        Ok(())
+    // keys are stored without suffixes, lookups strip them from Move::to_san output
+    fn book_key(san: &str) -> String {
+        match san.trim_end_matches(SAN_SUFFIXES) {
+            "0-0" => "O-O".to_string(),
+            "0-0-0" => "O-O-O".to_string(),
+            m => m.to_string()
+        }
     }
 
     fn add_game(&mut self, moves: &[String]) {
@@ -161,7 +121,7 @@ impl OpeningBook {
         let mut current = &self.root;
         
         for mv in moves {
-            match current.children.get(mv) {
+            match current.children.get(mv.trim_end_matches(SAN_SUFFIXES)) {
                 Some(child) => current = child,
                 None => return None,
             }
@@ -173,14 +133,7 @@ impl OpeningBook {
     }
 
     pub fn to_move(&self, san: &str, board: &mut Board) -> Option<Move> {
-        let mut found = None;
-        for m in board.get_total_legal_moves(None) {
-            if m.to_san(board) == san {
-                found = Some(m);
-            }
-        }
-        
-        found
+        Move::from_san(san, board).ok()
     }
 
     pub fn print_statistics(&self) {
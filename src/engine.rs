@@ -59,7 +59,8 @@ impl Engine {
         match self.engine_type {
             EngineType::Minimax => {
                 let engine = self.minimax.as_mut().unwrap();
-                engine.search(board, depth.unwrap_or(7), f64::NEG_INFINITY, f64::INFINITY, true).moves.first().cloned()
+                let result = engine.search(board, depth.unwrap_or(7), f64::NEG_INFINITY, f64::INFINITY, true);
+                result.moves.first().map(|&m| board.unpack(m))
             },
             EngineType::MCTS => {
                 let engine = self.mcts.as_mut().unwrap();
@@ -81,7 +82,8 @@ impl Engine {
         match self.engine_type {
             EngineType::Minimax => {
                 let engine = self.minimax.as_mut().unwrap();
-                engine.iterative_deepening(board, depth, time_limit).moves.first().cloned()
+                let result = engine.iterative_deepening(board, depth, time_limit);
+                result.moves.first().map(|&m| board.unpack(m))
             },
             EngineType::MCTS => {
                 let engine = self.mcts.as_mut().unwrap();
@@ -1,8 +1,10 @@
 use std::usize;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{board::{Board, ResultType}, r#const::*, piece::{PartialPiece, PieceColor, PieceType}, pieces::{bitboard::{A_FILE_INV, H_FILE_INV}, queen::get_controlled_squares_queen}};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct EvaluationResult {
     pub white: f64,
     pub black: f64
@@ -39,7 +41,7 @@ pub fn evaluate(board: &mut Board) -> EvaluationResult {
             white: 0.0,
             black: 10000000000.0
         },
-        ResultType::Draw | ResultType::Stalemate => return EvaluationResult {
+        ResultType::Stalemate | ResultType::Draw(_) | ResultType::Claimable(_) => return EvaluationResult {
             white: 0.0,
             black: 0.0
         },
@@ -48,7 +50,7 @@ pub fn evaluate(board: &mut Board) -> EvaluationResult {
 
     let mut value = EvaluationResult::default();
 
-    for piece in board.pieces.values() {
+    for piece in board.pieces() {
         if piece.piece_type == PieceType::King { continue; }
         match piece.color {
             PieceColor::White => value.white += piece.piece_type.to_value() as f64,
@@ -75,7 +77,7 @@ pub fn evaluate_pawns(board: &mut Board) -> EvaluationResult {
 
     let mut values = EvaluationResult::default();
 
-    for pawn in board.pieces.values().filter(|p| p.piece_type == PieceType::Pawn) {
+    for pawn in board.pieces().filter(|p| p.piece_type == PieceType::Pawn) {
         match pawn.color {
             PieceColor::White => files_white[pawn.pos.x] += 1,
             PieceColor::Black => files_black[pawn.pos.x] += 1
@@ -116,8 +118,8 @@ pub fn evaluate_pawns(board: &mut Board) -> EvaluationResult {
 pub fn evaluate_mobility(board: &mut Board) -> EvaluationResult {
     let mut values = EvaluationResult::default();
 
-    for (index, piece) in &board.pieces {
-        let value = board.mobility_cache.get(index).unwrap_or(&0.0);
+    for piece in board.pieces() {
+        let value = board.mobility_cache.get(&piece.index).unwrap_or(&0.0);
 
         match piece.color {
             PieceColor::White => values.white += value,
@@ -131,7 +133,7 @@ pub fn evaluate_mobility(board: &mut Board) -> EvaluationResult {
 pub fn evaluate_piece_safety(board: &mut Board) -> EvaluationResult {
     let mut value = EvaluationResult::default();
 
-    for piece in board.pieces.values() {
+    for piece in board.pieces() {
         if piece.piece_type == PieceType::King { continue; }
 
         let pos = piece.pos;
@@ -180,7 +182,7 @@ pub fn evaluate_position(board: &Board, piece_type: PieceType, x: usize, y: usiz
 pub fn evaluate_positions(board: &Board) -> EvaluationResult {
     let mut value = EvaluationResult::default();
 
-    for piece in board.pieces.values() {
+    for piece in board.pieces() {
         let x = piece.pos.x;
         let y = piece.pos.y;
 
@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use crate::board::{Board, MoveInfo, ResultType};
+use crate::moves::{Move, MoveParseError, SanError};
+use crate::piece::PieceColor;
+
+#[derive(Clone)]
+pub struct GameMove {
+    pub m: Move,
+    pub info: MoveInfo,
+    // written against the position before the move, with check and mate suffixes
+    pub san: String,
+    // time left on the mover's clock once the move was made
+    pub clock: Option<Duration>
+}
+
+// a game owns its starting position and the line played from it. undone moves stay in the line
+// until a different move is played, so they can be redone
+#[derive(Clone)]
+pub struct Game {
+    start: Board,
+    board: Board,
+    moves: Vec<GameMove>,
+    ply: usize,
+    tags: Vec<(String, String)>,
+    result: Option<ResultType>
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Game::from_board(Board::startpos())
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        Game {
+            start: board.clone(),
+            board,
+            moves: Vec::new(),
+            ply: 0,
+            tags: Vec::new(),
+            result: None
+        }
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // every move of the line, including the ones that were undone
+    pub fn moves(&self) -> &[GameMove] {
+        &self.moves
+    }
+
+    // the moves leading to the current position
+    pub fn played(&self) -> &[GameMove] {
+        &self.moves[..self.ply]
+    }
+
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn last_move(&self) -> Option<&GameMove> {
+        self.played().last()
+    }
+
+    pub fn sans(&self) -> Vec<String> {
+        self.played().iter().map(|gm| gm.san.clone()).collect()
+    }
+
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.start.chess960 = chess960;
+        self.board.chess960 = chess960;
+    }
+
+    // the move has to be legal in the current position, any undone moves are dropped
+    pub fn play(&mut self, m: Move) {
+        let san = m.to_san(&self.board);
+
+        self.moves.truncate(self.ply);
+        self.result = None;
+
+        let info = self.board.make_move(&m);
+        self.moves.push(GameMove { m, info, san, clock: None });
+        self.ply += 1;
+    }
+
+    // passes the turn, which isn't allowed in check. written as 0000 in UCI and -- in SAN
+    pub fn play_null(&mut self) -> bool {
+        let color = self.board.turn;
+        let Some(info) = self.board.make_null_move() else {
+            return false;
+        };
+
+        self.moves.truncate(self.ply);
+        self.result = None;
+
+        self.moves.push(GameMove { m: Move::null(color), info, san: "--".to_string(), clock: None });
+        self.ply += 1;
+
+        true
+    }
+
+    pub fn play_uci(&mut self, uci: &str) -> Result<(), MoveParseError> {
+        if uci == "0000" {
+            return if self.play_null() { Ok(()) } else { Err(MoveParseError::NullMove) };
+        }
+
+        let m = Move::from_uci(uci, &mut self.board)?;
+        self.play(m);
+
+        Ok(())
+    }
+
+    pub fn play_san(&mut self, san: &str) -> Result<(), SanError> {
+        if san == "--" {
+            return if self.play_null() { Ok(()) } else { Err(SanError::IllegalMove(san.to_string())) };
+        }
+
+        let m = Move::from_san(san, &self.board)?;
+        self.play(m);
+
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> bool {
+        if self.ply == 0 {
+            return false;
+        }
+
+        self.ply -= 1;
+        let GameMove { m, info, .. } = &self.moves[self.ply];
+        if m.is_null() {
+            self.board.unmake_null_move(info);
+        } else {
+            self.board.unmake_move(m, info);
+        }
+
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        if self.ply == self.moves.len() {
+            return false;
+        }
+
+        let game_move = &mut self.moves[self.ply];
+        game_move.info = if game_move.m.is_null() {
+            self.board.make_null_move().expect("null move replayed in check")
+        } else {
+            self.board.make_move(&game_move.m)
+        };
+        self.ply += 1;
+
+        true
+    }
+
+    pub fn jump_to(&mut self, ply: usize) -> bool {
+        if ply > self.moves.len() {
+            return false;
+        }
+
+        while self.ply > ply {
+            self.undo();
+        }
+        while self.ply < ply {
+            self.redo();
+        }
+
+        true
+    }
+
+    // clocks are attached to the last move played, like PGN %clk comments
+    pub fn set_clock(&mut self, clock: Duration) {
+        if let Some(game_move) = self.ply.checked_sub(1).and_then(|ply| self.moves.get_mut(ply)) {
+            game_move.clock = Some(clock);
+        }
+    }
+
+    // the latest clock the side recorded, moves played without one are skipped
+    pub fn clock(&self, color: PieceColor) -> Option<Duration> {
+        self.played().iter().rev()
+            .filter(|gm| gm.m.piece_color == color)
+            .find_map(|gm| gm.clock)
+    }
+
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    // tags keep the order they were first set in
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string()))
+        }
+    }
+
+    // a result decided off the board (adjudication, a claimed draw) only holds at the end of the line
+    pub fn result(&self) -> ResultType {
+        match &self.result {
+            Some(result) if self.ply == self.moves.len() => result.clone(),
+            _ => self.board.get_result()
+        }
+    }
+
+    pub fn set_result(&mut self, result: ResultType) {
+        self.result = Some(result);
+    }
+
+    pub fn claim_draw(&mut self) -> bool {
+        match self.board.get_result() {
+            ResultType::Claimable(reason) => {
+                self.moves.truncate(self.ply);
+                self.result = Some(ResultType::Draw(reason));
+                true
+            },
+            _ => false
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
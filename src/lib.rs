@@ -1,5 +1,9 @@
 pub mod board;
+pub mod game;
+pub mod movegen;
 pub mod moves;
+pub mod perft;
+pub mod pgn;
 pub mod piece;
 pub mod pieces;
 pub mod evaluation;
@@ -9,4 +13,7 @@ pub mod protocol;
 pub mod mcts;
 pub mod engine;
 pub mod book;
-pub mod server;
\ No newline at end of file
+pub mod server;
+pub mod threats;
+pub mod render;
+pub mod zobrist;
\ No newline at end of file
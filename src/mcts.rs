@@ -102,7 +102,7 @@ impl Mcts {
             self.nodes_visited += 1;
         }
 
-        if board.get_result().is_end() {
+        if is_terminal(&board.get_result()) {
             return path;
         }
 
@@ -110,7 +110,7 @@ impl Mcts {
             let legal_moves = board.get_total_legal_moves(None);
 
             let mut scores: Vec<(Move, f64)> = legal_moves.into_iter()
-                .map(|m| (m.clone(), Minimax::evaluate_move_base(&m, board)))
+                .map(|m| (m.clone(), Minimax::evaluate_move_base(m.pack(), board)))
                 .collect();
 
             scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
@@ -147,7 +147,7 @@ impl Mcts {
         let mut rng = rand::rng();
         let mut plies = 0;
 
-        while !board.get_result().is_end() && plies < MCTS_MAX_PLIES && !self.is_stopping {
+        while !is_terminal(&board.get_result()) && plies < MCTS_MAX_PLIES && !self.is_stopping {
             let legal_moves = board.get_total_legal_moves(None);
             if legal_moves.is_empty() {
                 break;
@@ -203,7 +203,7 @@ impl Mcts {
             ResultType::BlackCheckmate => {
                 if turn == PieceColor::Black { 1.0 } else { 0.0 }
             },
-            ResultType::Draw | ResultType::Stalemate => 0.5,
+            ResultType::Stalemate | ResultType::Draw(_) | ResultType::Claimable(_) => 0.5,
             ResultType::None | ResultType::NotCached => {
                 let eval = evaluate(board);
                 let score = match turn {
@@ -270,6 +270,11 @@ impl Mcts {
     }
 }
 
+// a playout stops at a draw that could be claimed, a player would take it rather than play on
+fn is_terminal(result: &ResultType) -> bool {
+    result.is_end() || matches!(result, ResultType::Claimable(_))
+}
+
 #[test]
 fn test_mcts() {
     let mut board = Board::from_fen("2k2r2/1ppp4/pn5q/8/8/8/3B1PPP/1Q4K1 w - - 0 1");
@@ -277,4 +282,4 @@ fn test_mcts() {
 
     let best_move = mcts.iterative_deepening(&mut board, 20000, 10);
     println!("Best move: {:?}", best_move);
-}
\ No newline at end of file
+}
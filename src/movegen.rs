@@ -0,0 +1,311 @@
+use crate::board::Board;
+use crate::moves::{MoveList, PackedMove, Position};
+use crate::piece::{PieceColor, PieceType};
+use crate::pieces::attacks;
+use crate::pieces::bitboard::{RANK_1, RANK_2, RANK_7, RANK_8};
+use crate::pieces::king::get_castling_move;
+
+// pseudo-legal moves follow the piece rules but may leave the mover's king in check. they're
+// cheap to produce in stages, and Board::is_legal only has to run on the moves actually tried
+
+// captures, en passant and promotions, the moves quiescence looks at
+pub fn generate_captures(board: &Board, color: PieceColor, moves: &mut MoveList) {
+    let enemy = board.bb.pieces_of_color(color.opposite());
+
+    generate_pawn_moves(board, color, true, moves);
+
+    for (piece_type, set) in piece_sets(board, color) {
+        for from in squares(set) {
+            let targets = attacks_from(piece_type, color, from, board.bb.all_pieces) & enemy;
+            push_moves(board, piece_type, color, from, targets, moves);
+        }
+    }
+}
+
+// everything generate_captures leaves out, castling included
+pub fn generate_quiets(board: &Board, color: PieceColor, moves: &mut MoveList) {
+    generate_pawn_moves(board, color, false, moves);
+
+    for (piece_type, set) in piece_sets(board, color) {
+        for from in squares(set) {
+            let targets = attacks_from(piece_type, color, from, board.bb.all_pieces) & board.bb.empty_squares;
+            push_moves(board, piece_type, color, from, targets, moves);
+        }
+    }
+
+    if let Some(king) = board.get_king(color) {
+        for kingside in [true, false] {
+            if board.castling.can_castle(color, kingside) {
+                if let Some(m) = get_castling_move(&king, board, kingside) {
+                    moves.push(m);
+                }
+            }
+        }
+    }
+}
+
+pub fn generate_pseudo_legal(board: &Board, color: PieceColor, moves: &mut MoveList) {
+    generate_captures(board, color, moves);
+    generate_quiets(board, color, moves);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    HashMove,
+    OrderCaptures,
+    GoodCaptures,
+    OrderQuiets,
+    Quiets,
+    BadCaptures,
+    Done
+}
+
+// hands out the legal moves of the side to move one stage at a time, so a cutoff on an early
+// move never pays for generating or sorting the later stages. the order is the hash move, the
+// captures that don't lose material, the killers, the other quiet moves and the losing captures
+pub struct MovePicker {
+    color: PieceColor,
+    stage: Stage,
+    captures_only: bool,
+    hash_move: Option<PackedMove>,
+    killers: [Option<PackedMove>; 2],
+    captures: MoveList,
+    quiets: MoveList,
+    bad_captures: MoveList,
+    quiets_generated: bool,
+    index: usize
+}
+
+impl MovePicker {
+    pub fn new(board: &Board, captures_only: bool) -> Self {
+        MovePicker {
+            color: board.turn,
+            stage: Stage::HashMove,
+            captures_only,
+            hash_move: None,
+            killers: [None; 2],
+            captures: MoveList::new(),
+            quiets: MoveList::new(),
+            bad_captures: MoveList::new(),
+            quiets_generated: false,
+            index: 0
+        }
+    }
+
+    // the hash move comes from a table slot that other positions share, it's only tried when
+    // this position actually generates it
+    pub fn with_hints(board: &Board, hash_move: Option<PackedMove>, killers: [Option<PackedMove>; 2]) -> Self {
+        MovePicker {
+            hash_move,
+            killers,
+            ..MovePicker::new(board, false)
+        }
+    }
+
+    // `order` sorts the captures and the quiet moves once each is generated, best move first
+    pub fn next<F: FnMut(&mut MoveList, &mut Board)>(&mut self, board: &mut Board, mut order: F) -> Option<PackedMove> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::OrderCaptures;
+                    generate_captures(board, self.color, &mut self.captures);
+
+                    let Some(m) = self.hash_move else { continue };
+                    let generated = self.captures.contains(&m) || (!self.captures_only && {
+                        generate_quiets(board, self.color, &mut self.quiets);
+                        self.quiets_generated = true;
+                        self.quiets.contains(&m)
+                    });
+
+                    if generated && board.is_legal(m) {
+                        return Some(m);
+                    }
+                    self.hash_move = None;
+                },
+                Stage::OrderCaptures => {
+                    order(&mut self.captures, board);
+                    self.index = 0;
+                    self.stage = Stage::GoodCaptures;
+                },
+                Stage::GoodCaptures => {
+                    while self.index < self.captures.len() {
+                        let m = self.captures[self.index];
+                        self.index += 1;
+
+                        if Some(m) == self.hash_move {
+                            continue;
+                        }
+                        if !board.see_ge(&board.unpack(m), 0) {
+                            self.bad_captures.push(m);
+                            continue;
+                        }
+                        if board.is_legal(m) {
+                            return Some(m);
+                        }
+                    }
+
+                    self.index = 0;
+                    self.stage = if self.captures_only { Stage::BadCaptures } else { Stage::OrderQuiets };
+                },
+                Stage::OrderQuiets => {
+                    if !self.quiets_generated {
+                        generate_quiets(board, self.color, &mut self.quiets);
+                    }
+                    order(&mut self.quiets, board);
+
+                    // the killers move to the front, keeping the order of everything else
+                    let mut front = 0;
+                    for killer in self.killers.into_iter().flatten() {
+                        if let Some(i) = self.quiets[front..].iter().position(|&m| m == killer) {
+                            self.quiets[front..=front + i].rotate_right(1);
+                            front += 1;
+                        }
+                    }
+
+                    self.index = 0;
+                    self.stage = Stage::Quiets;
+                },
+                Stage::Quiets => {
+                    while self.index < self.quiets.len() {
+                        let m = self.quiets[self.index];
+                        self.index += 1;
+
+                        if Some(m) != self.hash_move && board.is_legal(m) {
+                            return Some(m);
+                        }
+                    }
+
+                    self.index = 0;
+                    self.stage = Stage::BadCaptures;
+                },
+                Stage::BadCaptures => {
+                    while self.index < self.bad_captures.len() {
+                        let m = self.bad_captures[self.index];
+                        self.index += 1;
+
+                        if board.is_legal(m) {
+                            return Some(m);
+                        }
+                    }
+
+                    self.stage = Stage::Done;
+                },
+                Stage::Done => return None
+            }
+        }
+    }
+}
+
+fn generate_pawn_moves(board: &Board, color: PieceColor, captures: bool, moves: &mut MoveList) {
+    let (pawns, start_rank, last_rank) = if color == PieceColor::White {
+        (board.bb.white_pawns, RANK_2, RANK_8)
+    } else {
+        (board.bb.black_pawns, RANK_7, RANK_1)
+    };
+
+    let enemy = board.bb.pieces_of_color(color.opposite());
+    let en_passant = match board.target_square {
+        Some(target) if board.turn == color => target.to_bitboard(),
+        _ => 0
+    };
+
+    for from in squares(pawns) {
+        let single_push = forward(1u64 << from, color) & board.bb.empty_squares;
+        let attacks = attacks::pawn(from, color);
+
+        if captures {
+            for to in squares((attacks & enemy) | (single_push & last_rank)) {
+                let captured = board.mailbox[to].map(|piece| piece.piece_type);
+                let capture_flag = if captured.is_some() { PackedMove::CAPTURE } else { 0 };
+
+                if (1u64 << to) & last_rank != 0 {
+                    // queen, rook, bishop, knight
+                    for promotion in (0..4).rev() {
+                        let m = PackedMove::new(square(from), square(to), PackedMove::PROMOTION | capture_flag | promotion, PieceType::Pawn, captured);
+                        push(board, color, m, moves);
+                    }
+                } else {
+                    push(board, color, PackedMove::new(square(from), square(to), PackedMove::CAPTURE, PieceType::Pawn, captured), moves);
+                }
+            }
+
+            if attacks & en_passant != 0 {
+                let to = en_passant.trailing_zeros() as usize;
+                push(board, color, PackedMove::new(square(from), square(to), PackedMove::EN_PASSANT, PieceType::Pawn, Some(PieceType::Pawn)), moves);
+            }
+        } else {
+            if single_push & !last_rank != 0 {
+                let to = single_push.trailing_zeros() as usize;
+                push(board, color, PackedMove::new(square(from), square(to), PackedMove::QUIET, PieceType::Pawn, None), moves);
+            }
+
+            let double_push = forward(single_push, color) & board.bb.empty_squares;
+            if (1u64 << from) & start_rank != 0 && double_push != 0 {
+                let to = double_push.trailing_zeros() as usize;
+                push(board, color, PackedMove::new(square(from), square(to), PackedMove::DOUBLE_PUSH, PieceType::Pawn, None), moves);
+            }
+        }
+    }
+}
+
+fn push_moves(board: &Board, piece_type: PieceType, color: PieceColor, from: usize, targets: u64, moves: &mut MoveList) {
+    for to in squares(targets) {
+        let captured = board.mailbox[to].map(|piece| piece.piece_type);
+        let flag = if captured.is_some() { PackedMove::CAPTURE } else { PackedMove::QUIET };
+
+        push(board, color, PackedMove::new(square(from), square(to), flag, piece_type, captured), moves);
+    }
+}
+
+fn push(board: &Board, color: PieceColor, m: PackedMove, moves: &mut MoveList) {
+    moves.push(if gives_check(board, color, m) { m.with_check() } else { m });
+}
+
+// direct checks only, the same ones the legal generator marks
+fn gives_check(board: &Board, color: PieceColor, m: PackedMove) -> bool {
+    let king = if color == PieceColor::White { board.bb.black_king } else { board.bb.white_king };
+    let occupied = (board.bb.all_pieces & !m.from().to_bitboard()) | m.to().to_bitboard();
+    let to = m.to();
+
+    attacks_from(m.promote_to().unwrap_or(m.piece_type()), color, to.x + to.y * 8, occupied) & king != 0
+}
+
+fn attacks_from(piece_type: PieceType, color: PieceColor, square: usize, occupied: u64) -> u64 {
+    match piece_type {
+        PieceType::Pawn => attacks::pawn(square, color),
+        PieceType::Knight => attacks::knight(square),
+        PieceType::Bishop => attacks::bishop(square, occupied),
+        PieceType::Rook => attacks::rook(square, occupied),
+        PieceType::Queen => attacks::queen(square, occupied),
+        PieceType::King => attacks::king(square)
+    }
+}
+
+// every piece set of the color except the pawns, which move on their own rules
+fn piece_sets(board: &Board, color: PieceColor) -> impl Iterator<Item = (PieceType, u64)> {
+    let sets = board.bb.pieces_of(color);
+
+    [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King]
+        .into_iter()
+        .map(move |piece_type| (piece_type, sets[piece_type.index()]))
+}
+
+fn forward(set: u64, color: PieceColor) -> u64 {
+    if color == PieceColor::White { set >> 8 } else { set << 8 }
+}
+
+fn squares(mut set: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if set == 0 {
+            return None;
+        }
+
+        let square = set.trailing_zeros() as usize;
+        set &= set - 1;
+        Some(square)
+    })
+}
+
+fn square(index: usize) -> Position {
+    Position { x: index % 8, y: index / 8 }
+}
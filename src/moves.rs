@@ -1,10 +1,12 @@
 use std::fmt;
+use std::ops::{Deref, DerefMut};
 use crate::board::Board;
 use crate::evaluation::evaluate_position;
-use crate::r#const::{MVV_LVA_VALUES, PIECE_VALUES};
+use crate::r#const::{MAX_MOVES, MVV_LVA_VALUES, PIECE_VALUES};
 use crate::piece::{PieceType, PieceColor, Piece};
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Clone, Copy, Hash)]
 pub struct Position {
@@ -85,6 +87,25 @@ impl PartialEq for Position {
     }
 }
 
+// squares are written in algebraic notation, "e4"
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:?}", self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let square = String::deserialize(deserializer)?;
+        let chars: Vec<char> = square.chars().collect();
+
+        match chars[..] {
+            [file, rank] => Move::parse_square(file, rank),
+            _ => None
+        }.ok_or_else(|| de::Error::custom(format!("invalid square '{}'", square)))
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Vector {
     pub x: isize,
@@ -138,6 +159,50 @@ pub enum MoveType {
     EnPassant
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum SanError {
+    InvalidSyntax(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+    MissingPromotion(String)
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanError::InvalidSyntax(san) => write!(f, "invalid SAN syntax '{}'", san),
+            SanError::IllegalMove(san) => write!(f, "illegal move '{}'", san),
+            SanError::AmbiguousMove(san) => write!(f, "ambiguous move '{}'", san),
+            SanError::MissingPromotion(san) => write!(f, "missing promotion piece in '{}'", san)
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveParseError {
+    InvalidLength(String),
+    InvalidSquare(String),
+    InvalidPromotion(String),
+    IllegalMove(String),
+    NullMove
+}
+
+impl fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveParseError::InvalidLength(uci) => write!(f, "invalid UCI move length '{}'", uci),
+            MoveParseError::InvalidSquare(uci) => write!(f, "invalid square in '{}'", uci),
+            MoveParseError::InvalidPromotion(uci) => write!(f, "invalid promotion piece in '{}'", uci),
+            MoveParseError::IllegalMove(uci) => write!(f, "illegal move '{}'", uci),
+            MoveParseError::NullMove => write!(f, "null move '0000'")
+        }
+    }
+}
+
+impl std::error::Error for MoveParseError {}
+
 #[derive(Clone)]
 pub struct Move {
     pub from: Position,
@@ -153,23 +218,30 @@ pub struct Move {
 
 impl fmt::Debug for Move {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let promotion_char = if let Some(piece_type) = self.promote_to {
-            match piece_type {
-                PieceType::Knight => "n",
-                PieceType::Bishop => "b",
-                PieceType::Rook => "r",
-                PieceType::Queen => "q",
-                _ => unreachable!()
-            }
-        } else {
-            ""
-        };
-
-        write!(f, "{:?}{:?}{}", self.from, self.to, promotion_char)
+        write!(f, "{}", self.to_uci())
     }
 }
 
 impl Move {
+    // a pass, only kept to record null moves in a game. nothing moves, from and to are the same square
+    pub fn null(color: PieceColor) -> Move {
+        Move {
+            from: Position { x: 0, y: 0 },
+            to: Position { x: 0, y: 0 },
+            move_type: vec![],
+            captured: None,
+            promote_to: None,
+            piece_index: 0,
+            piece_color: color,
+            piece_type: PieceType::King,
+            with: None
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.from == self.to
+    }
+
     pub fn hash(&self) -> usize {
         let mut hasher = DefaultHasher::new();
         self.from.hash(&mut hasher);
@@ -181,24 +253,15 @@ impl Move {
         hasher.finish() as usize
     }
 
+    pub fn is_irreversible(&self) -> bool {
+        self.piece_type == PieceType::Pawn || self.move_type.contains(&MoveType::Capture)
+    }
+
     pub fn mvv_lva(&self) -> f64 {
         if !self.move_type.contains(&MoveType::Capture) || self.captured.is_none() {
             return 0.0;
         }
-        let victim = self.captured.as_ref().unwrap().piece_type.index();
-        let aggressor = self.piece_type.index();
-        
-        let ordering_value = MVV_LVA_VALUES[victim][aggressor] as f64;
-        
-        let victim_value = PIECE_VALUES[victim];
-        let aggressor_value = PIECE_VALUES[aggressor];
-        
-        if aggressor_value > victim_value {
-            let trade_penalty = (aggressor_value - victim_value) * 2.0;
-            return ordering_value - trade_penalty;
-        }
-
-        ordering_value
+        mvv_lva(self.captured.as_ref().unwrap().piece_type, self.piece_type)
     }
 
     pub fn ps_table(&self, board: &Board) -> f64 {
@@ -210,37 +273,110 @@ impl Move {
         evaluate_position(board, self.piece_type, x, y_index)
     }
 
+    pub fn pack(&self) -> PackedMove {
+        let capture = self.move_type.contains(&MoveType::Capture);
+
+        let flag = if self.move_type.contains(&MoveType::Castling) {
+            if self.to.x == 6 { PackedMove::KING_CASTLE } else { PackedMove::QUEEN_CASTLE }
+        } else if let Some(promotion) = self.promote_to {
+            PackedMove::PROMOTION | (promotion.index() as u32 - 1) | if capture { PackedMove::CAPTURE } else { 0 }
+        } else if self.move_type.contains(&MoveType::EnPassant) {
+            PackedMove::EN_PASSANT
+        } else if capture {
+            PackedMove::CAPTURE
+        } else if self.piece_type == PieceType::Pawn && self.from.y.abs_diff(self.to.y) == 2 {
+            PackedMove::DOUBLE_PUSH
+        } else {
+            PackedMove::QUIET
+        };
+
+        let captured = if capture { self.captured.as_ref().map(|p| p.piece_type) } else { None };
+        let packed = PackedMove::new(self.from, self.to, flag, self.piece_type, captured);
+
+        if self.move_type.contains(&MoveType::Check) { packed.with_check() } else { packed }
+    }
+
+    pub fn to_uci(&self) -> String {
+        if self.is_null() {
+            return "0000".to_string();
+        }
+
+        let mut uci = format!("{:?}{:?}", self.from, self.to);
+
+        if let Some(promotion) = self.promote_to {
+            uci.push(Move::piece_char(promotion).to_ascii_lowercase());
+        }
+
+        uci
+    }
+
+    pub fn from_uci(uci: &str, board: &mut Board) -> Result<Move, MoveParseError> {
+        let (from, to, promote_to) = Move::parse_uci(uci)?;
+
+        let chess960 = board.chess960;
+
+        // castling is written as king-takes-rook in Chess960 and as the king's destination otherwise
+        board.get_total_legal_moves(None)
+            .into_iter()
+            .find(|m| m.from == from && m.promote_to == promote_to && match (&m.with, m.move_type.contains(&MoveType::Castling)) {
+                (Some(rook), true) => to == rook.pos || (!chess960 && to == m.to),
+                _ => to == m.to
+            })
+            .ok_or_else(|| MoveParseError::IllegalMove(uci.to_string()))
+    }
+
+    pub fn to_uci_chess960(&self) -> String {
+        match &self.with {
+            Some(rook) if self.move_type.contains(&MoveType::Castling) => format!("{:?}{:?}", self.from, rook.pos),
+            _ => self.to_uci()
+        }
+    }
+
+    pub fn to_lan(&self, board: &Board) -> String {
+        if self.move_type.contains(&MoveType::Castling) {
+            return format!("{}{}", self.castling_str(), self.check_suffix(board));
+        }
+
+        let mut lan = String::new();
+
+        if self.piece_type != PieceType::Pawn {
+            lan.push(Move::piece_char(self.piece_type));
+        }
+
+        lan.push_str(&format!("{:?}", self.from));
+        lan.push(if self.move_type.contains(&MoveType::Capture) { 'x' } else { '-' });
+        lan.push_str(&format!("{:?}", self.to));
+
+        if let Some(promotion) = self.promote_to {
+            lan.push('=');
+            lan.push(Move::piece_char(promotion));
+        }
+
+        lan.push_str(self.check_suffix(board));
+
+        lan
+    }
+
     pub fn to_san(&self, board: &Board) -> String {
         if self.move_type.contains(&MoveType::Castling) {
-            if self.to.x == 6 {
-                return "O-O".to_string();
-            } else {
-                return "O-O-O".to_string();
-            }
+            return format!("{}{}", self.castling_str(), self.check_suffix(board));
         }
 
         let mut san = String::new();
 
         if self.piece_type != PieceType::Pawn {
-            san.push(match self.piece_type {
-                PieceType::King => 'K',
-                PieceType::Queen => 'Q',
-                PieceType::Rook => 'R',
-                PieceType::Bishop => 'B',
-                PieceType::Knight => 'N',
-                _ => unreachable!()
-            });
+            san.push(Move::piece_char(self.piece_type));
             
             let mut same_pieces = Vec::new();
             
-            for (i, piece) in board.pieces.iter() {
+            for piece in board.pieces() {
                 if piece.piece_type == self.piece_type && 
                    piece.color == self.piece_color && 
-                   *i != self.piece_index {
+                   piece.index != self.piece_index {
                     
-                    let moves = board.get_legal_moves(*i);
+                    let moves = board.get_legal_moves(piece.index);
                     if moves.iter().any(|m| m.to == self.to) {
-                        same_pieces.push(i);
+                        same_pieces.push(piece);
                     }
                 }
             }
@@ -249,17 +385,17 @@ impl Move {
                 let from_file = self.from.x;
                 let from_rank = self.from.y;
                 
-                let need_file = same_pieces.iter().any(|&index| {
-                    board.pieces[&index].pos.x == from_file
+                let shares_file = same_pieces.iter().any(|piece| {
+                    piece.pos.x == from_file
                 });
                 
-                let need_rank = same_pieces.iter().any(|&index| {
-                    board.pieces[&index].pos.y == from_rank
+                let shares_rank = same_pieces.iter().any(|piece| {
+                    piece.pos.y == from_rank
                 });
                 
-                if !need_rank {
+                if !shares_file {
                     san.push("abcdefgh".chars().nth(from_file).unwrap());
-                } else if !need_file {
+                } else if !shares_rank {
                     san.push(char::from_digit(8 - from_rank as u32, 10).unwrap());
                 } else {
                     san.push("abcdefgh".chars().nth(from_file).unwrap());
@@ -275,22 +411,204 @@ impl Move {
             san.push('x');
         }
 
-        san.push("abcdefgh".chars().nth(self.to.x).unwrap());
-        san.push(char::from_digit(8 - self.to.y as u32, 10).unwrap());
+        san.push_str(&format!("{:?}", self.to));
 
-        if self.move_type.contains(&MoveType::Promotion) && self.promote_to.is_some() {
+        if let Some(promotion) = self.promote_to {
             san.push('=');
-            san.push(match self.promote_to.unwrap() {
-                PieceType::Queen => 'Q',
-                PieceType::Rook => 'R',
-                PieceType::Bishop => 'B',
-                PieceType::Knight => 'N',
-                _ => unreachable!()
-            });
+            san.push(Move::piece_char(promotion));
         }
 
+        san.push_str(self.check_suffix(board));
+
         san
     }
+
+    fn castling_str(&self) -> &'static str {
+        if self.to.x == 6 {
+            "O-O"
+        } else {
+            "O-O-O"
+        }
+    }
+
+    fn check_suffix(&self, board: &Board) -> &'static str {
+        let mut scratch = board.clone();
+        scratch.make_move(self);
+
+        let check = scratch.get_check(scratch.turn);
+        if check.checked == 0 && check.double_checked == 0 {
+            ""
+        } else if scratch.has_legal_moves(scratch.turn) {
+            "+"
+        } else {
+            "#"
+        }
+    }
+
+    fn piece_char(piece_type: PieceType) -> char {
+        match piece_type {
+            PieceType::Pawn => 'P',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Rook => 'R',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K'
+        }
+    }
+
+    pub fn from_san(san: &str, board: &Board) -> Result<Move, SanError> {
+        let invalid = || SanError::InvalidSyntax(san.to_string());
+
+        let trimmed = san.trim()
+            .trim_end_matches("e.p.")
+            .trim_end()
+            .trim_end_matches(['+', '#', '!', '?']);
+
+        if trimmed.is_empty() {
+            return Err(invalid());
+        }
+
+        let legal_moves = board.generate_legal_moves(board.turn, false);
+
+        let castling = trimmed.replace(['0', 'o'], "O");
+        if castling == "O-O" || castling == "O-O-O" {
+            let kingside = castling == "O-O";
+            return legal_moves.into_iter()
+                .find(|m| m.move_type.contains(&MoveType::Castling) && (m.to.x == 6) == kingside)
+                .ok_or_else(|| SanError::IllegalMove(san.to_string()));
+        }
+
+        let mut chars: Vec<char> = trimmed.chars()
+            .filter(|c| !matches!(c, 'x' | ':' | '-'))
+            .collect();
+
+        let piece_type = match chars.first() {
+            Some('N') => PieceType::Knight,
+            Some('B') => PieceType::Bishop,
+            Some('R') => PieceType::Rook,
+            Some('Q') => PieceType::Queen,
+            Some('K') => PieceType::King,
+            Some(_) => PieceType::Pawn,
+            None => return Err(invalid())
+        };
+
+        if piece_type != PieceType::Pawn {
+            chars.remove(0);
+        }
+
+        let mut promote_to = None;
+        if piece_type == PieceType::Pawn && chars.len() >= 3 {
+            let last = chars[chars.len() - 1];
+            let promotion = match last.to_ascii_uppercase() {
+                'N' => Some(PieceType::Knight),
+                'B' => Some(PieceType::Bishop),
+                'R' => Some(PieceType::Rook),
+                'Q' => Some(PieceType::Queen),
+                _ => None
+            };
+
+            if let Some(promotion) = promotion {
+                if chars[chars.len() - 2] == '=' {
+                    chars.truncate(chars.len() - 2);
+                    promote_to = Some(promotion);
+                } else if chars[chars.len() - 2].is_ascii_digit() {
+                    chars.truncate(chars.len() - 1);
+                    promote_to = Some(promotion);
+                }
+            }
+        }
+
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+
+        let to = Move::parse_square(chars[chars.len() - 2], chars[chars.len() - 1]).ok_or_else(invalid)?;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &c in &chars[..chars.len() - 2] {
+            match c {
+                'a'..='h' if from_file.is_none() => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' if from_rank.is_none() => from_rank = Some(8 - (c as usize - '0' as usize)),
+                _ => return Err(invalid())
+            }
+        }
+
+        let candidates: Vec<Move> = legal_moves.into_iter()
+            .filter(|m| m.piece_type == piece_type &&
+                m.to == to &&
+                !m.move_type.contains(&MoveType::Castling) &&
+                from_file.is_none_or(|x| m.from.x == x) &&
+                from_rank.is_none_or(|y| m.from.y == y) &&
+                (promote_to.is_none() || m.promote_to == promote_to)
+            )
+            .collect();
+
+        if candidates.iter().any(|m| m.promote_to.is_some()) && promote_to.is_none() {
+            return Err(SanError::MissingPromotion(san.to_string()));
+        }
+
+        match candidates.len() {
+            0 => Err(SanError::IllegalMove(san.to_string())),
+            1 => Ok(candidates.into_iter().next().unwrap()),
+            _ => Err(SanError::AmbiguousMove(san.to_string()))
+        }
+    }
+
+    fn parse_uci(uci: &str) -> Result<(Position, Position, Option<PieceType>), MoveParseError> {
+        if uci == "0000" {
+            return Err(MoveParseError::NullMove);
+        }
+
+        let chars: Vec<char> = uci.chars().collect();
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(MoveParseError::InvalidLength(uci.to_string()));
+        }
+
+        let invalid_square = || MoveParseError::InvalidSquare(uci.to_string());
+        let from = Move::parse_square(chars[0], chars[1]).ok_or_else(invalid_square)?;
+        let to = Move::parse_square(chars[2], chars[3]).ok_or_else(invalid_square)?;
+
+        let promote_to = match chars.get(4) {
+            Some('q') => Some(PieceType::Queen),
+            Some('r') => Some(PieceType::Rook),
+            Some('b') => Some(PieceType::Bishop),
+            Some('n') => Some(PieceType::Knight),
+            Some(_) => return Err(MoveParseError::InvalidPromotion(uci.to_string())),
+            None => None
+        };
+
+        Ok((from, to, promote_to))
+    }
+
+    pub fn notation(&self, board: &Board) -> MoveNotation {
+        MoveNotation { uci: self.to_uci(), san: self.to_san(board) }
+    }
+
+    fn parse_square(file: char, rank: char) -> Option<Position> {
+        if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return None;
+        }
+
+        Some(Position {
+            x: file as usize - 'a' as usize,
+            y: 8 - (rank as usize - '0' as usize)
+        })
+    }
+}
+
+// a move only has a stable text form relative to the position it is played in,
+// so it is serialized through its notation and resolved against a board again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveNotation {
+    pub uci: String,
+    pub san: String
+}
+
+impl MoveNotation {
+    pub fn to_move(&self, board: &mut Board) -> Result<Move, MoveParseError> {
+        Move::from_uci(&self.uci, board)
+    }
 }
 
 impl PartialEq for Move {
@@ -302,6 +620,250 @@ impl PartialEq for Move {
 
 impl Eq for Move {}
 
+fn mvv_lva(victim: PieceType, aggressor: PieceType) -> f64 {
+    let victim = victim.index();
+    let aggressor = aggressor.index();
+
+    let ordering_value = MVV_LVA_VALUES[victim][aggressor] as f64;
+
+    let victim_value = PIECE_VALUES[victim];
+    let aggressor_value = PIECE_VALUES[aggressor];
+
+    if aggressor_value > victim_value {
+        let trade_penalty = (aggressor_value - victim_value) * 2.0;
+        return ordering_value - trade_penalty;
+    }
+
+    ordering_value
+}
+
+pub(crate) const PIECE_TYPES: [PieceType; 6] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King];
+
+// bits 0-5 from, 6-11 to, 12-15 flags, 16-18 moving piece, 19-21 captured piece + 1, 22 check
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PackedMove(u32);
+
+impl PackedMove {
+    pub const NULL: PackedMove = PackedMove(0);
+
+    pub const QUIET: u32 = 0;
+    pub const DOUBLE_PUSH: u32 = 1;
+    pub const KING_CASTLE: u32 = 2;
+    pub const QUEEN_CASTLE: u32 = 3;
+    pub const CAPTURE: u32 = 4;
+    pub const EN_PASSANT: u32 = 5;
+    // the low two bits pick knight, bishop, rook or queen, CAPTURE may be set on top
+    pub const PROMOTION: u32 = 8;
+
+    const CHECK: u32 = 1 << 22;
+
+    pub fn new(from: Position, to: Position, flag: u32, piece_type: PieceType, captured: Option<PieceType>) -> PackedMove {
+        let captured = captured.map_or(0, |p| p.index() as u32 + 1);
+
+        PackedMove(
+            (from.x + from.y * 8) as u32
+            | ((to.x + to.y * 8) as u32) << 6
+            | flag << 12
+            | (piece_type.index() as u32) << 16
+            | captured << 19
+        )
+    }
+
+    pub fn with_check(self) -> PackedMove {
+        PackedMove(self.0 | PackedMove::CHECK)
+    }
+
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == PackedMove::NULL
+    }
+
+    pub fn from(&self) -> Position {
+        let square = (self.0 & 0x3f) as usize;
+        Position { x: square % 8, y: square / 8 }
+    }
+
+    pub fn to(&self) -> Position {
+        let square = ((self.0 >> 6) & 0x3f) as usize;
+        Position { x: square % 8, y: square / 8 }
+    }
+
+    pub fn flag(&self) -> u32 {
+        (self.0 >> 12) & 0xf
+    }
+
+    pub fn piece_type(&self) -> PieceType {
+        PIECE_TYPES[((self.0 >> 16) & 0x7) as usize]
+    }
+
+    pub fn captured(&self) -> Option<PieceType> {
+        match (self.0 >> 19) & 0x7 {
+            0 => None,
+            n => Some(PIECE_TYPES[n as usize - 1])
+        }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.flag() & PackedMove::CAPTURE != 0
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flag() == PackedMove::EN_PASSANT
+    }
+
+    pub fn is_castling(&self) -> bool {
+        self.flag() == PackedMove::KING_CASTLE || self.flag() == PackedMove::QUEEN_CASTLE
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.flag() & PackedMove::PROMOTION != 0
+    }
+
+    pub fn is_check(&self) -> bool {
+        self.0 & PackedMove::CHECK != 0
+    }
+
+    pub fn promote_to(&self) -> Option<PieceType> {
+        if self.is_promotion() {
+            Some(PIECE_TYPES[(self.flag() & 0x3) as usize + 1])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_irreversible(&self) -> bool {
+        self.piece_type() == PieceType::Pawn || self.is_capture()
+    }
+
+    pub fn mvv_lva(&self) -> f64 {
+        match self.captured() {
+            Some(victim) if self.is_capture() => mvv_lva(victim, self.piece_type()),
+            _ => 0.0
+        }
+    }
+
+    pub fn ps_table(&self, board: &Board) -> f64 {
+        let to = self.to();
+        let white = board.bb.white_pieces & self.from().to_bitboard() != 0;
+
+        let y_index = if white { to.y } else { 7 - to.y };
+
+        evaluate_position(board, self.piece_type(), to.x, y_index)
+    }
+
+    // castling is written as the king's destination, like Move::to_uci
+    pub fn to_uci(&self) -> String {
+        if self.is_null() {
+            return "0000".to_string();
+        }
+
+        let mut uci = format!("{:?}{:?}", self.from(), self.to());
+
+        if let Some(promotion) = self.promote_to() {
+            uci.push(Move::piece_char(promotion).to_ascii_lowercase());
+        }
+
+        uci
+    }
+}
+
+// packed moves are written as UCI. there's no way back without the position, which the flags
+// depend on, so read them with Move::from_uci instead
+impl Serialize for PackedMove {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_uci())
+    }
+}
+
+impl fmt::Debug for PackedMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+// fixed-capacity move buffer that lives on the stack. it also holds the pseudo-legal stages of
+// movegen, which can outnumber the 218 legal moves of the richest known position, so MAX_MOVES
+// keeps headroom above that and push checks it in debug builds
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [PackedMove; MAX_MOVES],
+    len: usize
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        MoveList {
+            moves: [PackedMove::NULL; MAX_MOVES],
+            len: 0
+        }
+    }
+
+    pub fn push(&mut self, m: PackedMove) {
+        debug_assert!(self.len < MAX_MOVES, "move list overflow");
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    pub fn retain<F: FnMut(&PackedMove) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+
+        for i in 0..self.len {
+            if f(&self.moves[i]) {
+                self.moves[kept] = self.moves[i];
+                kept += 1;
+            }
+        }
+
+        self.len = kept;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [PackedMove];
+
+    fn deref(&self) -> &[PackedMove] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [PackedMove] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a PackedMove;
+    type IntoIter = std::slice::Iter<'a, PackedMove>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Pin {
     pub position: Position,
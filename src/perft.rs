@@ -0,0 +1,222 @@
+use core::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::board::{Board, FenError};
+use crate::moves::{Move, MoveList};
+
+#[derive(Debug, Clone)]
+pub struct PerftOptions {
+    pub threads: usize,
+    // size of the node count cache in megabytes, 0 turns it off
+    pub hash_mb: usize
+}
+
+impl Default for PerftOptions {
+    fn default() -> Self {
+        PerftOptions {
+            threads: 1,
+            hash_mb: 0
+        }
+    }
+}
+
+// node counts of positions already walked, keyed by hash and the depth left below them
+pub struct PerftTable {
+    entries: Vec<(u64, u32, u64)>,  // (hash, depth, nodes)
+    mask: usize
+}
+
+impl PerftTable {
+    pub fn new(size_mb: usize) -> Self {
+        let num_entries = (size_mb * 1024 * 1024) / std::mem::size_of::<(u64, u32, u64)>();
+        let size = num_entries.max(1).next_power_of_two();
+        PerftTable {
+            entries: vec![(0, 0, 0); size],
+            mask: size - 1
+        }
+    }
+
+    fn index(&self, hash: u64, depth: u32) -> usize {
+        ((hash ^ (depth as u64).wrapping_mul(0x9E3779B97F4A7C15)) as usize) & self.mask
+    }
+
+    // depth 0 is never stored, so empty slots can't match
+    pub fn get(&self, hash: u64, depth: u32) -> Option<u64> {
+        let (stored_hash, stored_depth, nodes) = self.entries[self.index(hash, depth)];
+        (stored_hash == hash && stored_depth == depth).then_some(nodes)
+    }
+
+    pub fn store(&mut self, hash: u64, depth: u32, nodes: u64) {
+        let index = self.index(hash, depth);
+        self.entries[index] = (hash, depth, nodes);
+    }
+}
+
+pub fn perft(board: &mut Board, depth: u32) -> u64 {
+    count(board, depth, None)
+}
+
+pub fn perft_hashed(board: &mut Board, depth: u32, table: &mut PerftTable) -> u64 {
+    count(board, depth, Some(table))
+}
+
+fn count(board: &mut Board, depth: u32, mut table: Option<&mut PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut moves = MoveList::new();
+    board.generate_move_list(board.turn, false, &mut moves);
+
+    // the leaves are counted without being played
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    if let Some(nodes) = table.as_ref().and_then(|table| table.get(board.hash, depth)) {
+        return nodes;
+    }
+
+    let hash = board.hash;
+    let mut nodes = 0;
+    for &packed in &moves {
+        let m = board.unpack(packed);
+        let history = board.make_move(&m);
+        nodes += count(board, depth - 1, table.as_deref_mut());
+        board.unmake_move(&m, &history);
+    }
+
+    if let Some(table) = table {
+        table.store(hash, depth, nodes);
+    }
+
+    nodes
+}
+
+// node counts below every root move, in generation order. threads take root moves one at a
+// time, each with its own copy of the board and its own cache
+pub fn divide(board: &Board, depth: u32, options: &PerftOptions) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let moves = board.generate_legal_moves(board.turn, false);
+    let next = AtomicUsize::new(0);
+    let threads = options.threads.clamp(1, moves.len().max(1));
+
+    let mut counts: Vec<(usize, u64)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| {
+                let mut board = board.clone();
+                let mut table = (options.hash_mb > 0).then(|| PerftTable::new(options.hash_mb / threads));
+                let mut counts = Vec::new();
+
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(m) = moves.get(i) else { break };
+
+                    let history = board.make_move(m);
+                    counts.push((i, count(&mut board, depth - 1, table.as_mut())));
+                    board.unmake_move(m, &history);
+                }
+
+                counts
+            }))
+            .collect();
+
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    });
+
+    counts.sort_by_key(|&(i, _)| i);
+    counts.into_iter().map(|(i, nodes)| (moves[i].clone(), nodes)).collect()
+}
+
+// divide output in the usual `move: nodes` form, closed by the total
+pub fn format_divide(board: &Board, counts: &[(Move, u64)]) -> String {
+    let mut output = String::new();
+
+    for (m, nodes) in counts {
+        let uci = if board.chess960 { m.to_uci_chess960() } else { m.to_uci() };
+        output.push_str(&format!("{}: {}\n", uci, nodes));
+    }
+
+    let total: u64 = counts.iter().map(|(_, nodes)| nodes).sum();
+    output.push_str(&format!("\nNodes searched: {}\n", total));
+
+    output
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EpdError {
+    InvalidFen(FenError),
+    InvalidCount(String)
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EpdError::InvalidFen(err) => write!(f, "Invalid FEN: {}", err),
+            EpdError::InvalidCount(field) => write!(f, "Invalid perft count: {}", field)
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+// one line of a perft suite: `<fen> ;D1 20 ;D2 400 ...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpdPosition {
+    pub fen: String,
+    pub counts: Vec<(u32, u64)>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub depth: u32,
+    pub expected: u64,
+    pub found: u64
+}
+
+impl EpdPosition {
+    // the move counters may be left out, like in most suites
+    pub fn parse(line: &str) -> Result<EpdPosition, EpdError> {
+        let mut fields = line.split(';');
+        let position = fields.next().unwrap_or("").trim();
+
+        let fen = match position.split_whitespace().count() {
+            4 => format!("{} 0 1", position),
+            _ => position.to_string()
+        };
+
+        let counts = fields
+            .map(|field| {
+                let field = field.trim();
+                let invalid = || EpdError::InvalidCount(field.to_string());
+
+                let (depth, nodes) = field.split_once(' ').ok_or_else(invalid)?;
+                let depth = depth.strip_prefix('D').and_then(|d| d.parse().ok()).ok_or_else(invalid)?;
+                let nodes = nodes.trim().parse().map_err(|_| invalid())?;
+
+                Ok((depth, nodes))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Board::try_from_fen(&fen).map_err(EpdError::InvalidFen)?;
+
+        Ok(EpdPosition { fen, counts })
+    }
+
+    // runs every reference count up to max_depth and returns the ones that don't match
+    pub fn check(&self, max_depth: Option<u32>, options: &PerftOptions) -> Vec<Mismatch> {
+        let board = Board::from_fen(&self.fen);
+
+        self.counts.iter()
+            .filter(|&&(depth, _)| max_depth.is_none_or(|max| depth <= max))
+            .filter_map(|&(depth, expected)| {
+                let found = if depth == 0 { 1 } else { divide(&board, depth, options).iter().map(|(_, nodes)| nodes).sum() };
+                (found != expected).then_some(Mismatch { depth, expected, found })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,570 @@
+use core::fmt;
+use std::time::Duration;
+
+use crate::board::{Board, FenError};
+use crate::game::Game;
+use crate::moves::SanError;
+use crate::piece::PieceColor;
+
+const LINE_WIDTH: usize = 80;
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*")
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PgnError {
+    UnterminatedString,
+    UnterminatedComment,
+    UnterminatedVariation,
+    UnexpectedToken(String),
+    InvalidNag(String),
+    InvalidFen(FenError),
+    IllegalMove(usize, SanError)
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::UnterminatedString => write!(f, "unterminated tag value"),
+            PgnError::UnterminatedComment => write!(f, "unterminated comment"),
+            PgnError::UnterminatedVariation => write!(f, "unterminated variation"),
+            PgnError::UnexpectedToken(token) => write!(f, "unexpected token '{}'", token),
+            PgnError::InvalidNag(nag) => write!(f, "invalid annotation '{}'", nag),
+            PgnError::InvalidFen(e) => write!(f, "invalid FEN tag: {}", e),
+            PgnError::IllegalMove(ply, e) => write!(f, "at ply {}: {}", ply, e)
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub san: String,
+    pub nags: Vec<u8>,
+    // a comment in front of the first move of a line
+    pub pre_comment: Option<String>,
+    pub comment: Option<String>,
+    // alternatives to this move, each starting from the position before it
+    pub variations: Vec<Vec<PgnMove>>
+}
+
+impl PgnMove {
+    pub fn new(san: &str) -> Self {
+        PgnMove { san: san.to_string(), nags: Vec::new(), pre_comment: None, comment: None, variations: Vec::new() }
+    }
+
+    // the remaining clock from a [%clk h:mm:ss] command in the comment
+    pub fn clock(&self) -> Option<Duration> {
+        let comment = self.comment.as_ref()?;
+        let start = comment.find("[%clk")? + "[%clk".len();
+        let end = start + comment[start..].find(']')?;
+
+        let mut seconds = 0.0;
+        for part in comment[start..end].trim().split(':') {
+            seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+        }
+
+        Some(Duration::from_secs_f64(seconds))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    pub result: String
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    // replays the main line, variations and comments other than clocks are dropped
+    pub fn to_game(&self) -> Result<Game, PgnError> {
+        let mut board = match self.tag("FEN") {
+            Some(fen) => Board::try_from_fen(fen).map_err(PgnError::InvalidFen)?,
+            None => Board::startpos()
+        };
+        board.chess960 |= self.tag("Variant").is_some_and(|v| v.contains("960"));
+
+        let mut game = Game::from_board(board);
+        for (name, value) in &self.tags {
+            game.set_tag(name, value);
+        }
+        game.set_tag("Result", &self.result);
+
+        for (ply, m) in self.moves.iter().enumerate() {
+            game.play_san(&m.san).map_err(|e| PgnError::IllegalMove(ply + 1, e))?;
+
+            if let Some(clock) = m.clock() {
+                game.set_clock(clock);
+            }
+        }
+
+        Ok(game)
+    }
+
+    pub fn from_game(game: &Game) -> Self {
+        let mut tags: Vec<(String, String)> = game.tags().iter()
+            .filter(|(name, _)| name != "FEN" && name != "SetUp")
+            .cloned()
+            .collect();
+
+        let fen = game.start().to_fen();
+        if fen != Board::startpos().to_fen() {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), fen));
+        }
+
+        let moves = game.played().iter().map(|gm| PgnMove {
+            comment: gm.clock.map(|clock| {
+                let seconds = clock.as_secs();
+                format!("[%clk {}:{:02}:{:02}]", seconds / 3600, seconds / 60 % 60, seconds % 60)
+            }),
+            ..PgnMove::new(&gm.san)
+        }).collect();
+
+        // results decided off the board only survive through the Result tag
+        let result = match game.result().to_pgn() {
+            "*" if game.ply() == game.len() => game.tag("Result").unwrap_or("*"),
+            result => result
+        };
+
+        PgnGame { tags, moves, result: result.to_string() }
+    }
+
+    fn start(&self) -> (usize, PieceColor) {
+        let fields: Vec<&str> = self.tag("FEN").map_or(Vec::new(), |fen| fen.split_whitespace().collect());
+
+        let color = if fields.get(1) == Some(&"b") { PieceColor::Black } else { PieceColor::White };
+        let number = fields.get(5).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        (number, color)
+    }
+}
+
+// export format: the seven tag roster first, then the other tags, then the movetext wrapped at 80 columns
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = if name == "Result" { &self.result } else { self.tag(name).unwrap_or(default) };
+            writeln!(f, "[{} \"{}\"]", name, escape_tag(value))?;
+        }
+
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name) {
+                writeln!(f, "[{} \"{}\"]", name, escape_tag(value))?;
+            }
+        }
+        writeln!(f)?;
+
+        let mut tokens = Vec::new();
+        let (number, color) = self.start();
+        write_line(&mut tokens, &self.moves, number, color);
+        tokens.push(self.result.clone());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+
+        writeln!(f, "{}", line)
+    }
+}
+
+fn write_line(tokens: &mut Vec<String>, moves: &[PgnMove], mut number: usize, mut color: PieceColor) {
+    // black moves only get a number at the start of a line or after something interrupted the move pairs
+    let mut numbered = true;
+
+    for m in moves {
+        if let Some(comment) = &m.pre_comment {
+            push_comment(tokens, comment);
+            numbered = true;
+        }
+
+        if color == PieceColor::White {
+            tokens.push(format!("{}.", number));
+        } else if numbered {
+            tokens.push(format!("{}...", number));
+        }
+        numbered = false;
+
+        tokens.push(m.san.clone());
+        tokens.extend(m.nags.iter().map(|nag| format!("${}", nag)));
+
+        if let Some(comment) = &m.comment {
+            push_comment(tokens, comment);
+            numbered = true;
+        }
+
+        for variation in m.variations.iter().filter(|variation| !variation.is_empty()) {
+            let start = tokens.len();
+            write_line(tokens, variation, number, color);
+
+            tokens[start].insert(0, '(');
+            tokens.last_mut().unwrap().push(')');
+            numbered = true;
+        }
+
+        if color == PieceColor::Black {
+            number += 1;
+        }
+        color = color.opposite();
+    }
+}
+
+// comments are split into words so they wrap with the rest of the movetext
+fn push_comment(tokens: &mut Vec<String>, comment: &str) {
+    let escaped = comment.replace('\\', "\\\\").replace('{', "\\{").replace('}', "\\}");
+    let start = tokens.len();
+
+    tokens.extend(escaped.split_whitespace().map(|word| word.to_string()));
+    if tokens.len() == start {
+        tokens.push(String::new());
+    }
+
+    tokens[start].insert(0, '{');
+    tokens.last_mut().unwrap().push('}');
+}
+
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn write_game(game: &Game) -> String {
+    PgnGame::from_game(game).to_string()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    TagOpen,
+    TagClose,
+    Str(String),
+    Symbol(String),
+    Period,
+    Nag(u8),
+    Comment(String),
+    VariationOpen,
+    VariationClose
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::TagOpen => write!(f, "["),
+            Token::TagClose => write!(f, "]"),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Symbol(s) => write!(f, "{}", s),
+            Token::Period => write!(f, "."),
+            Token::Nag(nag) => write!(f, "${}", nag),
+            Token::Comment(c) => write!(f, "{{{}}}", c),
+            Token::VariationOpen => write!(f, "("),
+            Token::VariationClose => write!(f, ")")
+        }
+    }
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line_start: bool
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Lexer { chars: text.chars().peekable(), line_start: true }
+    }
+
+    fn rest_of_line(&mut self) -> String {
+        let mut line = String::new();
+        for c in self.chars.by_ref() {
+            if c == '\n' {
+                break;
+            }
+            line.push(c);
+        }
+        self.line_start = true;
+
+        line
+    }
+
+    fn comment(&mut self) -> Result<Token, PgnError> {
+        let mut comment = String::new();
+
+        // braces can't nest, a backslash escapes one inside the comment
+        while let Some(c) = self.chars.next() {
+            match c {
+                '}' => return Ok(Token::Comment(comment.trim().to_string())),
+                '\\' if matches!(self.chars.peek(), Some('{' | '}' | '\\')) => comment.push(self.chars.next().unwrap()),
+                c if c.is_whitespace() => {
+                    if !comment.ends_with(' ') {
+                        comment.push(' ');
+                    }
+                },
+                c => comment.push(c)
+            }
+        }
+
+        Err(PgnError::UnterminatedComment)
+    }
+
+    fn string(&mut self) -> Result<Token, PgnError> {
+        let mut value = String::new();
+
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(Token::Str(value)),
+                '\\' if matches!(self.chars.peek(), Some('"' | '\\')) => value.push(self.chars.next().unwrap()),
+                '\n' => break,
+                c => value.push(c)
+            }
+        }
+
+        Err(PgnError::UnterminatedString)
+    }
+
+    fn take_while(&mut self, first: char, f: impl Fn(char) -> bool) -> String {
+        let mut s = first.to_string();
+        while let Some(&c) = self.chars.peek() {
+            if !f(c) {
+                break;
+            }
+            s.push(c);
+            self.chars.next();
+        }
+
+        s
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let c = self.chars.next()?;
+            let line_start = std::mem::replace(&mut self.line_start, c == '\n');
+
+            let token = match c {
+                c if c.is_whitespace() => continue,
+                '%' if line_start => {
+                    self.rest_of_line();
+                    continue;
+                },
+                ';' => Ok(Token::Comment(self.rest_of_line().trim().to_string())),
+                '{' => self.comment(),
+                '"' => self.string(),
+                '[' => Ok(Token::TagOpen),
+                ']' => Ok(Token::TagClose),
+                '(' => Ok(Token::VariationOpen),
+                ')' => Ok(Token::VariationClose),
+                '.' => Ok(Token::Period),
+                '*' => Ok(Token::Symbol("*".to_string())),
+                '$' => {
+                    let nag = self.take_while('$', |c| c.is_ascii_digit());
+                    nag[1..].parse().map(Token::Nag).map_err(|_| PgnError::InvalidNag(nag))
+                },
+                '!' | '?' => {
+                    let suffix = self.take_while(c, |c| c == '!' || c == '?');
+                    match suffix.as_str() {
+                        "!" => Ok(Token::Nag(1)),
+                        "?" => Ok(Token::Nag(2)),
+                        "!!" => Ok(Token::Nag(3)),
+                        "??" => Ok(Token::Nag(4)),
+                        "!?" => Ok(Token::Nag(5)),
+                        "?!" => Ok(Token::Nag(6)),
+                        _ => Err(PgnError::InvalidNag(suffix))
+                    }
+                },
+                // -- is the null move
+                c if c.is_ascii_alphanumeric() || c == '-' => {
+                    Ok(Token::Symbol(self.take_while(c, |c| c.is_ascii_alphanumeric() || "_+#=:-/".contains(c))))
+                },
+                c => Err(PgnError::UnexpectedToken(c.to_string()))
+            };
+
+            return Some(token);
+        }
+    }
+}
+
+// reads every game of a PGN file in turn, a malformed game is reported and skipped
+pub struct PgnReader<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Result<Token, PgnError>>,
+    in_movetext: bool
+}
+
+impl<'a> PgnReader<'a> {
+    pub fn new(text: &'a str) -> Self {
+        PgnReader { lexer: Lexer::new(text), peeked: None, in_movetext: false }
+    }
+
+    fn next_token(&mut self) -> Option<Result<Token, PgnError>> {
+        self.peeked.take().or_else(|| self.lexer.next())
+    }
+
+    fn peek_token(&mut self) -> Option<&Result<Token, PgnError>> {
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next();
+        }
+        self.peeked.as_ref()
+    }
+
+    fn expect(&mut self) -> Result<Token, PgnError> {
+        match self.next_token() {
+            Some(token) => token,
+            None => Err(PgnError::UnexpectedToken("end of file".to_string()))
+        }
+    }
+
+    // only the end of the input ends the games, comments between two of them are skipped
+    fn read_game(&mut self) -> Result<Option<PgnGame>, PgnError> {
+        loop {
+            if let Some(game) = self.read_game_text()? {
+                return Ok(Some(game));
+            }
+            if self.peek_token().is_none() {
+                return Ok(None);
+            }
+        }
+    }
+
+    // None when nothing but comments came before the next tags or the end of the input
+    fn read_game_text(&mut self) -> Result<Option<PgnGame>, PgnError> {
+        let mut tags = Vec::new();
+        self.in_movetext = false;
+
+        // a comment in front of the tags sits outside the game, one in front of tagless
+        // movetext is the first move's
+        let mut leading: Option<String> = None;
+        while let Some(Ok(Token::Comment(_))) = self.peek_token() {
+            if let Some(Ok(Token::Comment(comment))) = self.next_token() {
+                leading = Some(match leading {
+                    Some(previous) => format!("{} {}", previous, comment),
+                    None => comment
+                });
+            }
+        }
+
+        while let Some(Ok(Token::TagOpen)) = self.peek_token() {
+            self.next_token();
+
+            let name = match self.expect()? {
+                Token::Symbol(name) => name,
+                token => return Err(PgnError::UnexpectedToken(token.to_string()))
+            };
+            let value = match self.expect()? {
+                Token::Str(value) => value,
+                token => return Err(PgnError::UnexpectedToken(token.to_string()))
+            };
+            match self.expect()? {
+                Token::TagClose => tags.push((name, value)),
+                token => return Err(PgnError::UnexpectedToken(token.to_string()))
+            }
+        }
+
+        self.in_movetext = true;
+        let pre_comment = if tags.is_empty() { leading } else { None };
+        let (moves, result) = self.read_line(false, pre_comment)?;
+
+        if tags.is_empty() && moves.is_empty() && result.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(PgnGame { tags, moves, result: result.unwrap_or_else(|| "*".to_string()) }))
+    }
+
+    // the main line ends at a result, the next game's tags or the end of the file, a variation at its ')'
+    fn read_line(&mut self, nested: bool, mut pre_comment: Option<String>) -> Result<(Vec<PgnMove>, Option<String>), PgnError> {
+        let mut moves: Vec<PgnMove> = Vec::new();
+
+        loop {
+            let token = match self.peek_token() {
+                None | Some(Ok(Token::TagOpen)) if nested => return Err(PgnError::UnterminatedVariation),
+                None | Some(Ok(Token::TagOpen)) => return Ok((moves, None)),
+                _ => self.next_token().unwrap()?
+            };
+
+            match token {
+                Token::Symbol(s) if RESULTS.contains(&s.as_str()) && !nested => return Ok((moves, Some(s))),
+                Token::Symbol(s) if s.chars().all(|c| c.is_ascii_digit()) => {},
+                Token::Symbol(s) if !RESULTS.contains(&s.as_str()) => {
+                    moves.push(PgnMove { pre_comment: pre_comment.take(), ..PgnMove::new(&s) });
+                },
+                Token::Period => {},
+                Token::Nag(nag) if !moves.is_empty() => moves.last_mut().unwrap().nags.push(nag),
+                Token::Comment(comment) => {
+                    let target = match moves.last_mut() {
+                        Some(m) => &mut m.comment,
+                        None => &mut pre_comment
+                    };
+                    *target = Some(match target.take() {
+                        Some(previous) => format!("{} {}", previous, comment),
+                        None => comment
+                    });
+                },
+                Token::VariationOpen if !moves.is_empty() => {
+                    let (variation, _) = self.read_line(true, None)?;
+                    moves.last_mut().unwrap().variations.push(variation);
+                },
+                Token::VariationClose if nested => return Ok((moves, None)),
+                token => return Err(PgnError::UnexpectedToken(token.to_string()))
+            }
+        }
+    }
+
+    // drops what is left of a broken game: up to its result, or up to tags that follow movetext
+    fn skip_game(&mut self) {
+        let mut in_tags = !self.in_movetext;
+
+        while let Some(token) = self.peek_token() {
+            match token {
+                Ok(Token::TagOpen) if !in_tags => return,
+                Ok(Token::Symbol(s)) if RESULTS.contains(&s.as_str()) => {
+                    self.next_token();
+                    return;
+                },
+                Ok(token) => in_tags = matches!(token, Token::TagClose | Token::TagOpen | Token::Str(_)),
+                Err(_) => {}
+            }
+            self.next_token();
+        }
+    }
+}
+
+impl Iterator for PgnReader<'_> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_game() {
+            Ok(game) => game.map(Ok),
+            Err(e) => {
+                self.skip_game();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+pub fn read_game(text: &str) -> Result<Game, PgnError> {
+    match PgnReader::new(text).next() {
+        Some(game) => game?.to_game(),
+        None => Ok(Game::new())
+    }
+}
@@ -1,7 +1,9 @@
-use crate::moves::{Move, Position};
+use serde::{Deserialize, Serialize};
 
+use crate::moves::Position;
 
-#[derive(Debug, Clone, PartialEq, Copy, Hash)]
+
+#[derive(Debug, Clone, PartialEq, Copy, Hash, Serialize, Deserialize)]
 pub enum PieceType {
     Pawn,
     Knight,
@@ -39,7 +41,7 @@ impl PieceType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, Serialize, Deserialize)]
 pub enum PieceColor {
     White,
     Black
@@ -54,14 +56,12 @@ impl PieceColor {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Piece {
     pub piece_type: PieceType,
     pub color: PieceColor,
     pub pos: Position,
-    pub index: usize,
-    pub legal_moves_cache: Vec<Move>,
-    pub legal_moves: bool
+    pub index: usize
 }
 
 impl Piece {
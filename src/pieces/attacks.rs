@@ -0,0 +1,221 @@
+use std::sync::OnceLock;
+
+use crate::piece::PieceColor;
+
+use super::bitboard::{AB_FILE_INV, A_FILE_INV, GH_FILE_INV, H_FILE_INV};
+
+const ROOK_DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+// found offline for this crate's square order (a8 = 0), one entry per square
+const ROOK_MAGICS: [u64; 64] = [
+    0x028000a140021080, 0x0240015000402008, 0x4100102002890141, 0x0480048010000800,
+    0x4a00200200081004, 0x0a00130410420028, 0x1b00008200070004, 0x0100010004229142,
+    0x0480800020804008, 0x0830401000402009, 0x1201004100102000, 0x0400801000800800,
+    0x0268800801800400, 0x040a000802001004, 0x0000800100800200, 0x4002000481005604,
+    0x8040008000209840, 0x0340002000300800, 0x9310008080102000, 0x0008008010000880,
+    0x0001010010080006, 0x0002010100080400, 0x4880040011081230, 0x1000220000410084,
+    0x5200401080008020, 0x3280400440201000, 0x0801004100200014, 0x0000090100201002,
+    0x4000040080080080, 0x4002020080800400, 0x0081002100141200, 0xc0500d120000a044,
+    0x10008040018001a1, 0x8000400088802001, 0x0400802000801000, 0x0450010008080080,
+    0x0261001005000801, 0x6402800200800400, 0x0000100804008201, 0x1000110942000284,
+    0xa084401028808002, 0x00100040a0014000, 0x0020001000808022, 0x8008008010008008,
+    0x1000050008010010, 0x0440406010480104, 0x020c089022240021, 0x0a00108041020004,
+    0x804c800644610100, 0x0020088020401680, 0x0200102001004100, 0x0020100221008b00,
+    0x0005000800108500, 0x1800800400020080, 0x954c100201080400, 0x0008049841040200,
+    0x2021002010800041, 0x800101804000a213, 0x0020001420090041, 0x4004042010010009,
+    0xa013000210080085, 0x1825000208140003, 0x408020a102100804, 0x0002002444009102
+];
+
+const BISHOP_MAGICS: [u64; 64] = [
+    0x1308020408042708, 0x0820283108408002, 0x0222188102020001, 0x0008060042028820,
+    0x0002021002810022, 0x0282010420100208, 0x40040c0228848010, 0x0400808811012010,
+    0x0031208410020051, 0x0100080800c08200, 0x0044300400a4ac20, 0x00003404158046c2,
+    0xf000440420100880, 0x1008021222204820, 0x4800020802021004, 0x8110088c04010400,
+    0x518a414010010202, 0x0006212098054102, 0x0444800802001600, 0x0040800802004084,
+    0x6402000412021040, 0x8804080080a00800, 0x22062188480c0404, 0x0800208c8a191006,
+    0x7408400048100144, 0x2230880022280109, 0x2000440140430200, 0x0002008008008002,
+    0x0141040002002108, 0x1210010402804100, 0x0004088004421030, 0x2002008a00404800,
+    0x81a4300400401490, 0x0034101400290900, 0x0000280410480020, 0x43e0900820140400,
+    0x0041080200102200, 0x0160008882050800, 0x00088204000080a1, 0x0080840100008094,
+    0x000409c410464010, 0x0842008404042024, 0x9001001090000200, 0x0000024010400208,
+    0x4001280104001840, 0x0292a00409000020, 0xe0020898108c0904, 0x00d9420409000840,
+    0x000d9804506420c8, 0x9012020121080c00, 0x2008011088240010, 0x1030038084042000,
+    0x8220001002020800, 0x4000400244010601, 0x2004091001020880, 0x8020622402418021,
+    0x1005002111207000, 0x10400922a2082020, 0x44000021008090a0, 0x8201000042842400,
+    0x0500008004105404, 0xa4000040280dc100, 0x0020400808051648, 0x0408100102040814
+];
+
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize
+}
+
+impl Magic {
+    fn index(&self, occupied: u64) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct AttackTables {
+    rook: Vec<Magic>,
+    bishop: Vec<Magic>,
+    attacks: Vec<u64>
+}
+
+static TABLES: OnceLock<AttackTables> = OnceLock::new();
+
+fn tables() -> &'static AttackTables {
+    TABLES.get_or_init(AttackTables::new)
+}
+
+// builds the tables up front so the first search doesn't pay for it
+pub fn init() {
+    tables();
+}
+
+pub fn rook(square: usize, occupied: u64) -> u64 {
+    let tables = tables();
+    tables.attacks[tables.rook[square].index(occupied)]
+}
+
+pub fn bishop(square: usize, occupied: u64) -> u64 {
+    let tables = tables();
+    tables.attacks[tables.bishop[square].index(occupied)]
+}
+
+pub fn queen(square: usize, occupied: u64) -> u64 {
+    rook(square, occupied) | bishop(square, occupied)
+}
+
+// leapers don't need tables, the same shifts the move generators use are cheap enough
+pub fn knight(square: usize) -> u64 {
+    let pos = 1u64 << square;
+
+    ((pos << 17) & A_FILE_INV) |
+    ((pos << 15) & H_FILE_INV) |
+    ((pos << 10) & AB_FILE_INV) |
+    ((pos >> 6) & AB_FILE_INV) |
+    ((pos >> 15) & A_FILE_INV) |
+    ((pos >> 17) & H_FILE_INV) |
+    ((pos << 6) & GH_FILE_INV) |
+    ((pos >> 10) & GH_FILE_INV)
+}
+
+pub fn king(square: usize) -> u64 {
+    let pos = 1u64 << square;
+
+    ((pos << 1) & A_FILE_INV) |
+    ((pos >> 1) & H_FILE_INV) |
+    (pos << 8) |
+    (pos >> 8) |
+    ((pos << 9) & A_FILE_INV) |
+    ((pos << 7) & H_FILE_INV) |
+    ((pos >> 7) & A_FILE_INV) |
+    ((pos >> 9) & H_FILE_INV)
+}
+
+// squares a pawn of the given color attacks from the square
+pub fn pawn(square: usize, color: PieceColor) -> u64 {
+    let pos = 1u64 << square;
+
+    if color == PieceColor::White {
+        ((pos & A_FILE_INV) >> 9) | ((pos & H_FILE_INV) >> 7)
+    } else {
+        ((pos & A_FILE_INV) << 7) | ((pos & H_FILE_INV) << 9)
+    }
+}
+
+// plain ray walk, used to fill the tables and as a reference for them
+pub fn sliding_attacks(square: usize, occupied: u64, directions: &[(isize, isize)]) -> u64 {
+    let mut attacks = 0u64;
+
+    for &(dx, dy) in directions {
+        let mut x = (square % 8) as isize + dx;
+        let mut y = (square / 8) as isize + dy;
+
+        while (0..8).contains(&x) && (0..8).contains(&y) {
+            let bit = 1u64 << (x + y * 8);
+            attacks |= bit;
+
+            if occupied & bit != 0 {
+                break;
+            }
+
+            x += dx;
+            y += dy;
+        }
+    }
+
+    attacks
+}
+
+pub fn rook_slow(square: usize, occupied: u64) -> u64 {
+    sliding_attacks(square, occupied, &ROOK_DIRECTIONS)
+}
+
+pub fn bishop_slow(square: usize, occupied: u64) -> u64 {
+    sliding_attacks(square, occupied, &BISHOP_DIRECTIONS)
+}
+
+// the last square of every ray doesn't change the attack set, so it's left out of the mask
+fn relevant_mask(square: usize, directions: &[(isize, isize)]) -> u64 {
+    let mut mask = 0u64;
+
+    for &(dx, dy) in directions {
+        let mut x = (square % 8) as isize + dx;
+        let mut y = (square / 8) as isize + dy;
+
+        while (0..8).contains(&(x + dx)) && (0..8).contains(&(y + dy)) {
+            mask |= 1u64 << (x + y * 8);
+            x += dx;
+            y += dy;
+        }
+    }
+
+    mask
+}
+
+impl AttackTables {
+    fn new() -> Self {
+        let mut attacks = Vec::new();
+
+        let rook = (0..64)
+            .map(|square| build_magic(square, ROOK_MAGICS[square], &ROOK_DIRECTIONS, &mut attacks))
+            .collect();
+        let bishop = (0..64)
+            .map(|square| build_magic(square, BISHOP_MAGICS[square], &BISHOP_DIRECTIONS, &mut attacks))
+            .collect();
+
+        AttackTables { rook, bishop, attacks }
+    }
+}
+
+fn build_magic(square: usize, magic: u64, directions: &[(isize, isize)], attacks: &mut Vec<u64>) -> Magic {
+    let mask = relevant_mask(square, directions);
+    let bits = mask.count_ones();
+
+    let magic = Magic {
+        mask,
+        magic,
+        shift: 64 - bits,
+        offset: attacks.len()
+    };
+
+    attacks.resize(attacks.len() + (1usize << bits), 0u64);
+
+    // enumerate every subset of the mask (carry-rippler)
+    let mut subset = 0u64;
+    loop {
+        attacks[magic.index(subset)] = sliding_attacks(square, subset, directions);
+
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+
+    magic
+}
@@ -1,116 +1,34 @@
 use crate::board::{Board, Control, ControlThreat, ControlType};
-use crate::moves::{Move, MoveType, Pin, Position, Vector};
+use crate::moves::{MoveList, PackedMove, Pin, Position, Vector};
 use crate::piece::{PartialPiece, Piece, PieceColor, PieceType};
 
-use super::bitboard::{A_FILE_INV, H_FILE_INV, RANK_1, RANK_8};
+use super::attacks;
 
 pub const BISHOP_DIRECTIONS: [Vector; 4] = [Vector { x: -1, y: -1 }, Vector { x: -1, y: 1 }, Vector { x: 1, y: -1 }, Vector { x: 1, y: 1}];
 
 pub fn generate_bishop_rays(pos: u64, occupied: u64, enemy_king: u64, let_through: bool) -> (u64, u64) {
-    let mut attacks = 0u64;
-    let mut obscured = 0u64;
-    let mut found_king = false;
-
-    let mut ray = pos;
-    while (ray & H_FILE_INV) != 0 && (ray & RANK_1) == 0 {
-        ray <<= 9;
-        attacks |= ray;
-
-        if ray & enemy_king != 0 {
-            found_king = true;
-        } else if found_king {
-            obscured |= ray;
-        }
-
-        if ray & occupied != 0 {
-            if ray & enemy_king != 0 {
-                found_king = true;
-                if !let_through { break; }
-            } else {
-                break;
-            }
-        }
-    }
-
-    found_king = false;
-    ray = pos;
-    while (ray & A_FILE_INV) != 0 && (ray & RANK_1) == 0 {
-        ray <<= 7;
-        attacks |= ray;
-
-        if ray & enemy_king != 0 {
-            found_king = true;
-        } else if found_king {
-            obscured |= ray;
-        }
-
-        if ray & occupied != 0 {
-            if ray & enemy_king != 0 {
-                found_king = true;
-                if !let_through { break; }
-            } else {
-                break;
-            }
-        }
-    }
-
-    found_king = false;
-    ray = pos;
-    while (ray & H_FILE_INV) != 0 && (ray & RANK_8) == 0 {
-        ray >>= 7;
-        attacks |= ray;
+    let square = pos.trailing_zeros() as usize;
+    let attacks = attacks::bishop(square, occupied);
 
-        if ray & enemy_king != 0 {
-            found_king = true;
-        } else if found_king {
-            obscured |= ray;
-        }
-
-        if ray & occupied != 0 {
-            if ray & enemy_king != 0 {
-                found_king = true;
-                if !let_through { break; }
-            } else {
-                break;
-            }
-        }
+    if !let_through || attacks & enemy_king == 0 {
+        return (attacks, 0u64);
     }
 
-    found_king = false;
-    ray = pos;
-    while (ray & A_FILE_INV) != 0 && (ray & RANK_8) == 0 {
-        ray >>= 9;
-        attacks |= ray;
-
-        if ray & enemy_king != 0 {
-            found_king = true;
-        } else if found_king {
-            obscured |= ray;
-        }
+    // squares behind the enemy king are only reachable with the king taken off the board
+    let through = attacks::bishop(square, occupied & !enemy_king);
 
-        if ray & occupied != 0 {
-            if ray & enemy_king != 0 {
-                found_king = true;
-                if !let_through { break; }
-            } else {
-                break;
-            }
-        }
-    }
-
-    (attacks, obscured)
+    (through, through & !attacks)
 }
 
-pub fn get_legal_moves_bishop(piece: &Piece, board: &Board) -> Vec<Move> {
+pub fn get_legal_moves_bishop(piece: &Piece, board: &Board, moves: &mut MoveList) {
     let pos = piece.pos.to_bitboard();
-    let mut moves = Vec::with_capacity(13);
 
     let pin_dir = board.is_pinned(piece.pos.y, piece.pos.x);
     let check_info = board.get_check(piece.color);
     
     let mut valid_squares = !0u64;
     if check_info.double_checked != 0u64 {
-        return moves;
+        return;
     }
     if check_info.block_mask != 0u64 { valid_squares = check_info.block_mask; }
 
@@ -150,25 +68,13 @@ pub fn get_legal_moves_bishop(piece: &Piece, board: &Board) -> Vec<Move> {
             }
         }
 
-        let is_capture = square & enemy != 0;
-        let captured = if is_capture { board.get_piece_at(to_pos.y, to_pos.x) } else { None };
-
-        moves.push(Move {
-            from: piece.pos,
-            to: to_pos,
-            move_type: vec![if is_capture { MoveType::Capture } else { MoveType::Normal }; 1],
-            captured,
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: None
-        });
+        let captured = if square & enemy != 0 { board.get_piece_at_bitboard(square) } else { None };
+
+        let flag = if captured.is_some() { PackedMove::CAPTURE } else { PackedMove::QUIET };
+        moves.push(PackedMove::new(piece.pos, to_pos, flag, piece.piece_type, captured.map(|(t, _)| t)));
 
         rem &= rem - 1;
     }
-
-    moves
 }
 
 pub fn get_controlled_squares_bishop(piece: &PartialPiece, board: &Board) -> Vec<Control> {
@@ -267,7 +173,7 @@ pub fn get_pins_bishop(piece: &Piece, board: &Board) -> Vec<Pin> {
                         break;
                     }
 
-                    enemy_piece = Some(enemy.clone());
+                    enemy_piece = Some(enemy);
                     potential_pin = true;
                 }
             }
@@ -1,12 +1,11 @@
 use crate::board::{Board, Control, ControlThreat, ControlType};
-use crate::moves::{Move, MoveType, Position};
-use crate::piece::{PartialPiece, Piece, PieceColor};
+use crate::moves::{MoveList, PackedMove, Position};
+use crate::piece::{PartialPiece, Piece, PieceColor, PieceType};
 
 use super::bitboard::{A_FILE_INV, H_FILE_INV};
 
-pub fn get_legal_moves_king(piece: &Piece, board: &Board) -> Vec<Move> {
+pub fn get_legal_moves_king(piece: &Piece, board: &Board, moves: &mut MoveList) {
     let pos = piece.pos.to_bitboard();
-    let mut moves = Vec::with_capacity(8);
 
     let king_moves = ((pos << 1) & A_FILE_INV) |
                      ((pos >> 1) & H_FILE_INV) |
@@ -33,76 +32,72 @@ pub fn get_legal_moves_king(piece: &Piece, board: &Board) -> Vec<Move> {
             continue;
         }
 
-        let captured = board.get_piece_at(to_pos.y, to_pos.x);
-        if captured.as_ref().is_some_and(|p| p.color == piece.color) {
+        let captured = board.get_piece_at_bitboard(1u64 << index);
+        if captured.is_some_and(|(_, color)| color == piece.color) {
             rem &= rem - 1;
             continue;
         }
 
-        moves.push(Move {
-            from: piece.pos,
-            to: to_pos,
-            move_type: vec![
-                if captured.is_some() { MoveType::Capture } else { MoveType::Normal }; 1
-            ],
-            captured,
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: None
-        });
+        let flag = if captured.is_some() { PackedMove::CAPTURE } else { PackedMove::QUIET };
+        moves.push(PackedMove::new(piece.pos, to_pos, flag, piece.piece_type, captured.map(|(t, _)| t)));
 
         rem &= rem - 1;
     }
 
-    let is_checked = !board.get_control_at(piece.pos.y, piece.pos.x, Some(piece.color.opposite()), true).is_empty();
-
-    let ifile = piece.pos.x as isize;
-
-    if board.castling.can_castle_ks(piece.color) && !is_checked && can_move_multifile(piece, board, piece.pos.y, vec![ ifile + 1, ifile + 2 ]) {
-        moves.push(Move {
-            from: piece.pos,
-            to: Position { x: piece.pos.x + 2, y: piece.pos.y },
-            captured: None,
-            move_type: vec![ MoveType::Castling ],
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: board.get_piece_at(piece.pos.y, piece.pos.x + 3)
-        })
+    for kingside in [true, false] {
+        if board.castling.can_castle(piece.color, kingside) {
+            if let Some(m) = get_castling_move(piece, board, kingside) {
+                moves.push(m);
+            }
+        }
     }
+}
 
-    if board.castling.can_castle_qs(piece.color) && !is_checked && can_move_multifile(piece, board, piece.pos.y, vec![ ifile - 1, ifile - 2 ]) && board.is_empty(piece.pos.y, Position::clamp(ifile - 3)) {
-        moves.push(Move {
-            from: piece.pos,
-            to: Position::from(ifile - 2, piece.pos.y as isize),
-            captured: None,
-            move_type: vec![ MoveType::Castling ],
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: board.get_piece_at(piece.pos.y, Position::clamp(ifile - 4))
-        })
+// handles Chess960 too: the king always lands on the c/g file and the rook on the d/f file
+pub(crate) fn get_castling_move(piece: &Piece, board: &Board, kingside: bool) -> Option<PackedMove> {
+    let rank = piece.pos.y;
+    if rank != if piece.color == PieceColor::White { 7 } else { 0 } {
+        return None;
     }
 
-    moves
-}
+    let king_file = piece.pos.x;
+    let rook_file = board.castling.rook_file(piece.color, kingside);
+    board.get_piece_at_bitboard(Position { x: rook_file, y: rank }.to_bitboard())
+        .filter(|&base| base == (PieceType::Rook, piece.color))?;
 
-fn can_move_to(piece: &Piece, board: &Board, rank: usize, file: usize, explicit: bool) -> bool {
-    if !Board::in_bounds(rank, file) { return false };
-    if board.get_control_at(rank, file, Some(piece.color.opposite()), true).len() > 0 { return false };
-    if explicit {
-        board.is_empty(rank, file)
-    } else {
-        board.square_free(rank, file, piece.color)
+    let (king_to, rook_to) = if kingside { (6, 5) } else { (2, 3) };
+
+    let king_path = king_file.min(king_to)..=king_file.max(king_to);
+    let rook_path = rook_file.min(rook_to)..=rook_file.max(rook_to);
+
+    if king_path.clone().chain(rook_path).any(|file| file != king_file && file != rook_file && !board.is_empty(rank, file)) {
+        return None;
     }
-}
 
-fn can_move_multifile(piece: &Piece, board: &Board, rank: usize, files: Vec<isize>) -> bool {
-    files.iter().all(|&i| can_move_to(piece, board, rank, Position::clamp(i), true))
+    if king_path.into_iter().any(|file| !board.get_control_at(rank, file, Some(piece.color.opposite()), true).is_empty()) {
+        return None;
+    }
+
+    // the castling rook may be the piece shielding the king's destination along the rank
+    for dir in [-1isize, 1] {
+        let mut file = king_to as isize + dir;
+        while (0..8).contains(&file) && file as usize != rook_to {
+            let f = file as usize;
+            if f != king_file && f != rook_file {
+                if let Some(p) = board.get_piece_at(rank, f) {
+                    if p.color != piece.color && (p.piece_type == PieceType::Rook || p.piece_type == PieceType::Queen) {
+                        return None;
+                    }
+                    break;
+                }
+            }
+            file += dir;
+        }
+    }
+
+    let flag = if kingside { PackedMove::KING_CASTLE } else { PackedMove::QUEEN_CASTLE };
+
+    Some(PackedMove::new(piece.pos, Position { x: king_to, y: rank }, flag, piece.piece_type, None))
 }
 
 pub fn get_controlled_squares_king(piece: &PartialPiece, board: &Board) -> Vec<Control> {
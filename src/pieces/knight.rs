@@ -1,19 +1,18 @@
 use crate::board::{Board, Control, ControlThreat, ControlType};
-use crate::moves::{Move, MoveType, Position};
+use crate::moves::{MoveList, PackedMove, Position};
 use crate::piece::{PartialPiece, Piece, PieceColor};
 use crate::pieces::bitboard::{AB_FILE_INV, A_FILE_INV, GH_FILE_INV, H_FILE_INV};
 
-pub fn get_legal_moves_knight(piece: &Piece, board: &Board) -> Vec<Move> {
+pub fn get_legal_moves_knight(piece: &Piece, board: &Board, moves: &mut MoveList) {
     let pos = piece.pos.to_bitboard();
-    let mut moves = Vec::with_capacity(8);
 
-    if board.is_pinned(piece.pos.y, piece.pos.x).is_some() { return moves };
+    if board.is_pinned(piece.pos.y, piece.pos.x).is_some() { return };
 
     let check_info = board.get_check(piece.color);
     
     let mut valid_squares = !0u64;
     if check_info.double_checked != 0u64 {
-        return moves;
+        return;
     }
     if check_info.block_mask != 0u64 { valid_squares = check_info.block_mask; }
 
@@ -44,30 +43,17 @@ pub fn get_legal_moves_knight(piece: &Piece, board: &Board) -> Vec<Move> {
             }
         }
 
-        let captured = board.get_piece_at(to_pos.y, to_pos.x);
-        if captured.as_ref().is_some_and(|p| p.color == piece.color) {
+        let captured = board.get_piece_at_bitboard(1u64 << index);
+        if captured.is_some_and(|(_, color)| color == piece.color) {
             rem &= rem - 1;
             continue;
         }
 
-        moves.push(Move {
-            from: piece.pos,
-            to: to_pos,
-            move_type: vec![
-                if captured.is_some() { MoveType::Capture } else { MoveType::Normal }; 1
-            ],
-            captured,
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: None
-        });
+        let flag = if captured.is_some() { PackedMove::CAPTURE } else { PackedMove::QUIET };
+        moves.push(PackedMove::new(piece.pos, to_pos, flag, piece.piece_type, captured.map(|(t, _)| t)));
 
         rem &= rem - 1;
     }
-
-    moves
 }
 
 pub fn get_controlled_squares_knight(piece: &PartialPiece, board: &Board) -> Vec<Control> {
@@ -4,4 +4,5 @@ pub mod bishop;
 pub mod rook;
 pub mod queen;
 pub mod king;
-pub mod bitboard;
\ No newline at end of file
+pub mod bitboard;
+pub mod attacks;
\ No newline at end of file
@@ -1,9 +1,9 @@
 use crate::board::{Board, Control, ControlThreat, ControlType};
-use crate::moves::{Move, MoveType, Position, Vector};
+use crate::moves::{MoveList, MoveType, PackedMove, Position, Vector};
 use crate::piece::{PartialPiece, Piece, PieceColor, PieceType};
 use crate::pieces::bitboard::{A_FILE_INV, H_FILE_INV, RANK_2, RANK_7};
 
-fn bitboard_to_move(piece: &Piece, pos: u64, move_type: MoveType, board: &Board, moves: &mut Vec<Move>, pin_dir: Option<Vector>) {
+fn bitboard_to_move(piece: &Piece, pos: u64, move_type: MoveType, board: &Board, moves: &mut MoveList, pin_dir: Option<Vector>) {
     if pos == 0 { return };
 
     let position = Position::from_bitboard(pos);
@@ -28,71 +28,45 @@ fn bitboard_to_move(piece: &Piece, pos: u64, move_type: MoveType, board: &Board,
     }
 
     if is_promotion {
-        for &promotion_type in &[PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight] {
-            let m = Move {
-                from: piece.pos,
-                to: position,
-                move_type: vec![move_type, MoveType::Promotion],
-                captured: if is_capture { board.get_piece_at(position.y, position.x) } else { None },
-                promote_to: Some(promotion_type),
-                piece_index: piece.index,
-                piece_color: piece.color,
-                piece_type: piece.piece_type,
-                with: None
-            };
-            moves.push(m);
+        let captured = if is_capture { board.get_piece_at_bitboard(pos).map(|(t, _)| t) } else { None };
+        let capture_flag = if is_capture { PackedMove::CAPTURE } else { 0 };
+
+        // queen, rook, bishop, knight
+        for promotion in (0..4).rev() {
+            moves.push(PackedMove::new(piece.pos, position, PackedMove::PROMOTION | capture_flag | promotion, piece.piece_type, captured));
         }
     } else {
         let is_en_passant = is_capture && board.is_empty(position.y, position.x);
 
-        let captured = if is_en_passant {
-            if piece.color == PieceColor::White {
-                board.get_piece_at(position.y + 1, position.x)
-            } else {
-                board.get_piece_at(position.y - 1, position.x)
-            }
+        let (flag, captured) = if is_en_passant {
+            (PackedMove::EN_PASSANT, Some(PieceType::Pawn))
         } else if is_capture {
-            board.get_piece_at(position.y, position.x)
+            (PackedMove::CAPTURE, board.get_piece_at_bitboard(pos).map(|(t, _)| t))
+        } else if piece.pos.y.abs_diff(position.y) == 2 {
+            (PackedMove::DOUBLE_PUSH, None)
         } else {
-            None
+            (PackedMove::QUIET, None)
         };
 
-        let mut move_type = vec![move_type];
-        if is_en_passant {
-            move_type.extend(vec![MoveType::EnPassant]);
-        }
-
-        let m = Move {
-            from: piece.pos,
-            to: position,
-            move_type,
-            captured,
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: None
-        };
-        moves.push(m);
+        moves.push(PackedMove::new(piece.pos, position, flag, piece.piece_type, captured));
     }
 }
 
-pub fn get_legal_moves_pawn(piece: &Piece, board: &Board) -> Vec<Move> {
+pub fn get_legal_moves_pawn(piece: &Piece, board: &Board, moves: &mut MoveList) {
     let pos = piece.pos.to_bitboard();
-    let mut moves = Vec::with_capacity(12);
 
     let pin_dir = board.is_pinned(piece.pos.y, piece.pos.x);
     let check_info = board.get_check(piece.color);
     
     let mut valid_squares = !0u64;
     if check_info.double_checked != 0u64 {
-        return moves;
+        return;
     }
     if check_info.block_mask != 0u64 { valid_squares = check_info.block_mask; }
 
     if let Some(pin) = pin_dir {
         if pin.x != 0 && pin.y == 0 {
-            return moves;
+            return;
         }
     }
 
@@ -128,10 +102,10 @@ pub fn get_legal_moves_pawn(piece: &Piece, board: &Board) -> Vec<Move> {
         ((pos & H_FILE_INV) << 9) & board.bb.white_pieces
     };
 
-    bitboard_to_move(piece, single_push & valid_squares, MoveType::Normal, board, &mut moves, pin_dir);
-    bitboard_to_move(piece, double_push & valid_squares, MoveType::Normal, board, &mut moves, pin_dir);
-    bitboard_to_move(piece, left_capture & valid_squares, MoveType::Capture, board, &mut moves, pin_dir);
-    bitboard_to_move(piece, right_capture & valid_squares, MoveType::Capture, board, &mut moves, pin_dir);
+    bitboard_to_move(piece, single_push & valid_squares, MoveType::Normal, board, moves, pin_dir);
+    bitboard_to_move(piece, double_push & valid_squares, MoveType::Normal, board, moves, pin_dir);
+    bitboard_to_move(piece, left_capture & valid_squares, MoveType::Capture, board, moves, pin_dir);
+    bitboard_to_move(piece, right_capture & valid_squares, MoveType::Capture, board, moves, pin_dir);
 
     if let Some(target_square) = board.target_square {
         let en_passant_pos = target_square.to_bitboard();
@@ -143,11 +117,9 @@ pub fn get_legal_moves_pawn(piece: &Piece, board: &Board) -> Vec<Move> {
         } & valid_squares;
 
         if !board.is_phantom_pinned(piece.pos.y, piece.pos.x) {
-            bitboard_to_move(piece, en_passant_capture, MoveType::Capture, board, &mut moves, pin_dir);
+            bitboard_to_move(piece, en_passant_capture, MoveType::Capture, board, moves, pin_dir);
         }
     }
-
-    moves
 }
 
 pub fn get_controlled_squares_pawn(piece: &PartialPiece, board: &Board) -> Vec<Control> {
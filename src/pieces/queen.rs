@@ -1,5 +1,5 @@
 use crate::board::{Board, Control, ControlThreat, ControlType};
-use crate::moves::{Move, MoveType, Pin, Position, Vector};
+use crate::moves::{MoveList, PackedMove, Pin, Position, Vector};
 use crate::piece::{PartialPiece, Piece, PieceColor, PieceType};
 
 use super::bishop::generate_bishop_rays;
@@ -23,16 +23,15 @@ fn generate_queen_rays(pos: u64, occupied: u64, enemy_king: u64, let_through: bo
     (b_attacks | r_attacks, b_obscured | r_obscured)
 }
 
-pub fn get_legal_moves_queen(piece: &Piece, board: &Board) -> Vec<Move> {
+pub fn get_legal_moves_queen(piece: &Piece, board: &Board, moves: &mut MoveList) {
     let pos = piece.pos.to_bitboard();
-    let mut moves = Vec::with_capacity(27);
 
     let pin_dir = board.is_pinned(piece.pos.y, piece.pos.x);
     let check_info = board.get_check(piece.color);
     
     let mut valid_squares = !0u64;
     if check_info.double_checked != 0u64 {
-        return moves;
+        return;
     }
     if check_info.block_mask != 0u64 { valid_squares = check_info.block_mask; }
 
@@ -67,25 +66,13 @@ pub fn get_legal_moves_queen(piece: &Piece, board: &Board) -> Vec<Move> {
             }
         }
 
-        let is_capture = square & enemy != 0;
-        let captured = if is_capture { board.get_piece_at(to_pos.y, to_pos.x) } else { None };
-        
-        moves.push(Move {
-            from: piece.pos,
-            to: to_pos,
-            move_type: vec![if is_capture { MoveType::Capture } else { MoveType::Normal }; 1],
-            captured,
-            promote_to: None,
-            piece_index: piece.index,
-            piece_color: piece.color,
-            piece_type: piece.piece_type,
-            with: None
-        });
+        let captured = if square & enemy != 0 { board.get_piece_at_bitboard(square) } else { None };
+
+        let flag = if captured.is_some() { PackedMove::CAPTURE } else { PackedMove::QUIET };
+        moves.push(PackedMove::new(piece.pos, to_pos, flag, piece.piece_type, captured.map(|(t, _)| t)));
 
         rem &= rem - 1;
     }
-
-    moves
 }
 
 pub fn get_controlled_squares_queen(piece: &PartialPiece, board: &Board) -> Vec<Control> {
@@ -184,7 +171,7 @@ pub fn get_pins_queen(piece: &Piece, board: &Board) -> Vec<Pin> {
                         break;
                     }
 
-                    enemy_piece = Some(enemy.clone());
+                    enemy_piece = Some(enemy);
                     potential_pin = true;
                 }
             }
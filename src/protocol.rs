@@ -1,23 +1,23 @@
 use std::{io::{self, Write}, path::Path};
 
-use crate::{board::Board, engine::{Engine, EngineType}, moves::{Move, MoveType}, piece::{PieceColor, PieceType}};
+use crate::{board::Board, engine::{Engine, EngineType}, game::Game, perft::{self, PerftOptions}, pgn, piece::PieceColor, pieces::attacks, render::{self, SvgOptions}};
 
 pub struct UciProtocol {
     pub engine: Engine,
-    board: Board,
+    game: Game,
     engine_type: EngineType,
     enable_book: bool,
-    move_history: Vec<String>
+    chess960: bool
 }
 
 impl UciProtocol {
     pub fn new() -> Self {
         UciProtocol { 
             engine: Engine::new(EngineType::Minimax, false), 
-            board: Board::startpos(),
+            game: Game::new(),
             engine_type: EngineType::Minimax, // default
             enable_book: false,
-            move_history: vec![]
+            chess960: false
         }
     }
 
@@ -28,8 +28,10 @@ impl UciProtocol {
             cmd if cmd.starts_with("position") => self.handle_position(cmd, &mut io::stdout())?,
             cmd if cmd.starts_with("go") => self.handle_go(cmd, &mut io::stdout())?,
             cmd if cmd.starts_with("setoption") => self.set_option(cmd, &mut io::stdout())?,
+            "d" => self.display(&mut io::stdout())?,
             "ucinewgame" => {
-                self.board = Board::startpos();
+                self.game = Game::new();
+                self.game.set_chess960(self.chess960);
                 self.engine.switch_to(self.engine_type);
                 self.engine.set_book_enabled(self.enable_book);
             },
@@ -49,6 +51,7 @@ impl UciProtocol {
         let stdin = io::stdin();
         let mut input = String::new();
 
+        attacks::init();
         self.engine.load_book(Path::new("book.pgn"))?;
 
         if let Some(book) = self.engine.book.as_ref() {
@@ -77,6 +80,7 @@ impl UciProtocol {
         println!("id author ggod");
         println!("option name EngineType type combo default Minimax var Minimax var MCTS");
         println!("option name EnableBook type check default false");
+        println!("option name UCI_Chess960 type check default false");
         println!("uciok");
     }
 
@@ -136,6 +140,21 @@ impl UciProtocol {
                     a => writeln!(writer, "info string Unknown enable book option: {}, current: {:?}", a, self.engine_type)?
                 }
             },
+            "uci_chess960" => {
+                match value.to_lowercase().as_str() {
+                    "true" => {
+                        writeln!(writer, "info string Setting UCI_Chess960 to true")?;
+                        self.chess960 = true;
+                        self.game.set_chess960(true);
+                    },
+                    "false" => {
+                        writeln!(writer, "info string Setting UCI_Chess960 to false")?;
+                        self.chess960 = false;
+                        self.game.set_chess960(false);
+                    },
+                    a => writeln!(writer, "info string Unknown UCI_Chess960 option: {}, current: {:?}", a, self.chess960)?
+                }
+            },
             a => writeln!(writer, "info string Unknown option: {}", a)?
         }
 
@@ -148,32 +167,34 @@ impl UciProtocol {
 
         match *pos_type {
             "startpos" => {
-                self.board = Board::startpos();
+                self.game = Game::new();
+                self.game.set_chess960(self.chess960);
                 self.engine.switch_to(self.engine_type);
                 self.engine.set_book_enabled(self.enable_book);
 
                 if let Some(moves_index) = parts.iter().position(|&p| p == "moves") {
-                    self.move_history.clear();
-                    for i in (moves_index + 1)..parts.len() {
-                        let uci_move = parts[i];
-                        writeln!(writer, "info String {uci_move}")?;
-                        self.move_uci(uci_move.trim());
-                    }
+                    self.apply_moves(&parts[(moves_index + 1)..], writer)?;
                 }
             },
             "fen" => {
-                if parts.len() >= 8 {
-                    let fen = parts[2..8].join(" ");
-                    self.board = Board::from_fen(&fen);
-
-                    if let Some(moves_index) = parts.iter().position(|&p| p == "moves") {
-                        self.move_history.clear();
-                        for i in (moves_index + 1)..parts.len() {
-                            let uci_move = parts[i];
-                            self.move_uci(uci_move.trim());
-                        }
+                let moves_index = parts.iter().position(|&p| p == "moves");
+                let fen = parts[2..moves_index.unwrap_or(parts.len())].join(" ");
+
+                match Board::try_from_fen(&fen) {
+                    Ok(board) => {
+                        let chess960 = board.chess960 || self.chess960;
+                        self.game = Game::from_board(board);
+                        self.game.set_chess960(chess960);
+                    },
+                    Err(e) => {
+                        writeln!(writer, "info string Invalid FEN {}: {}", fen, e)?;
+                        return Ok(());
                     }
                 }
+
+                if let Some(moves_index) = moves_index {
+                    self.apply_moves(&parts[(moves_index + 1)..], writer)?;
+                }
             },
             _ => {}
         }
@@ -183,6 +204,14 @@ impl UciProtocol {
 
     pub fn handle_go<T: Write>(&mut self, command: &str, writer: &mut T) -> io::Result<()> {
         let parts: Vec<&str> = command.split_whitespace().collect();
+
+        if parts.get(1) == Some(&"perft") {
+            let depth = parts.get(2).and_then(|d| d.parse().ok()).unwrap_or(1);
+            let counts = perft::divide(self.board(), depth, &PerftOptions::default());
+
+            return write!(writer, "{}", perft::format_divide(self.board(), &counts));
+        }
+
         let mut depth = 5;
         let mut time_limit = 5000;
         let mut wtime = None;
@@ -227,7 +256,7 @@ impl UciProtocol {
         if let Some(mt) = movetime {
             time_limit = mt;
         } else if wtime.is_some() || btime.is_some() {
-            let is_white = self.board.turn == PieceColor::White;
+            let is_white = self.game.board().turn == PieceColor::White;
             let time = if is_white { wtime } else { btime };
             let inc = if is_white { winc } else { binc };
 
@@ -242,11 +271,13 @@ impl UciProtocol {
             }
         }
 
-        let result = self.engine.iterative_deepening(&mut self.board, depth, time_limit, &self.move_history);
+        let mut board = self.game.board().clone();
+        let result = self.engine.iterative_deepening(&mut board, depth, time_limit, &self.game.sans());
 
         if let Some(best_move) = result.as_ref() {
-            writeln!(writer, "info string turn {:?} move clr {:?}", self.board.turn, best_move.piece_color)?;
-            writeln!(writer, "bestmove {}", self.move_to_uci(best_move))?;
+            writeln!(writer, "info string turn {:?} move clr {:?}", board.turn, best_move.piece_color)?;
+            let uci = if board.chess960 { best_move.to_uci_chess960() } else { best_move.to_uci() };
+            writeln!(writer, "bestmove {}", uci)?;
         } else {
             writeln!(writer, "bestmove 0000")?;
         }
@@ -254,63 +285,45 @@ impl UciProtocol {
         Ok(())
     }
 
-    fn move_uci(&mut self, uci_move: &str) {
-        if uci_move.len() < 4 {
-            return;
-        }
+    pub fn board(&self) -> &Board {
+        self.game.board()
+    }
 
-        println!("info string {uci_move} 2");
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
 
-        let from_file = (uci_move.chars().nth(0).unwrap() as u8 - b'a') as usize;
-        let from_rank = 8 - (uci_move.chars().nth(1).unwrap() as u8 - b'0') as usize;
-        let to_file = (uci_move.chars().nth(2).unwrap() as u8 - b'a') as usize;
-        let to_rank = 8 - (uci_move.chars().nth(3).unwrap() as u8 - b'0') as usize;
+    pub fn display<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        write!(writer, "{}", render::unicode(self.board(), PieceColor::White))?;
+        writeln!(writer, "Fen: {}", self.board().to_fen())?;
+        writeln!(writer, "Key: {:016X}", self.board().hash)?;
 
-        let legal_moves = self.board.get_total_legal_moves(None);
+        Ok(())
+    }
 
-        println!("info string legal_moves {:?}", legal_moves);
-        for m in legal_moves {
-            if m.from.x == from_file && m.from.y == from_rank && m.to.x == to_file && m.to.y == to_rank {
-                if uci_move.len() > 4 {
-                    println!("info string > 4 {uci_move}");
-                    if m.move_type.contains(&MoveType::Promotion) {
-                        self.board.make_move(&m);
-                        self.move_history.push(m.to_san(&self.board));
-                        break;
-                    }
-                } else {
-                    println!("info string turn bef {:?}", self.board.turn);
-                    self.board.make_move(&m);
-                    self.move_history.push(m.to_san(&self.board));
-                    println!("info string turn aft {:?}", self.board.turn);
-                    break;
-                }
-            }
-        }
+    pub fn to_svg(&self, flipped: bool, attacked_by: Option<PieceColor>) -> String {
+        let options = SvgOptions {
+            flipped,
+            last_move: self.game.last_move().map(|gm| (gm.m.from, gm.m.to)),
+            attacked_by,
+            ..SvgOptions::default()
+        };
+
+        render::svg(self.board(), &options)
+    }
+
+    pub fn to_pgn(&self) -> String {
+        pgn::write_game(&self.game)
     }
 
-    fn move_to_uci(&self, m: &Move) -> String {
-        let from_file = ('a' as u8 + m.from.x as u8) as char;
-        let from_rank = ('8' as u8 - m.from.y as u8) as char;
-        let to_file = ('a' as u8 + m.to.x as u8) as char;
-        let to_rank = ('8' as u8 - m.to.y as u8) as char;
-        
-        let mut uci = format!("{}{}{}{}", from_file, from_rank, to_file, to_rank);
-
-        if let Some(promotion) = &m.promote_to {
-            let promotion_char = match promotion {
-                PieceType::Queen => 'q',
-                PieceType::Rook => 'r',
-                PieceType::Bishop => 'b',
-                PieceType::Knight => 'n',
-                _ => ' '
-            };
-
-            if promotion_char != ' ' {
-                uci.push(promotion_char);
+    fn apply_moves<T: Write>(&mut self, moves: &[&str], writer: &mut T) -> io::Result<()> {
+        for uci_move in moves {
+            if let Err(e) = self.game.play_uci(uci_move) {
+                writeln!(writer, "info string Invalid move {}: {}", uci_move, e)?;
+                break;
             }
         }
 
-        uci
+        Ok(())
     }
-}
\ No newline at end of file
+}
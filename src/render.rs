@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use crate::board::Board;
+use crate::moves::Position;
+use crate::piece::{Piece, PieceColor, PieceType};
+
+const SQUARE_SIZE: usize = 45;
+const MARGIN: usize = 20;
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const LAST_MOVE: &str = "#cdd26a";
+const HIGHLIGHT: &str = "#6a9fd2";
+const CHECK: &str = "#e0494b";
+const ATTACKED: &str = "#d25b5b";
+const ARROW: &str = "#15781b";
+
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    pub flipped: bool,
+    pub coordinates: bool,
+    pub last_move: Option<(Position, Position)>,
+    pub highlights: Vec<Position>,
+    pub arrows: Vec<(Position, Position)>,
+    pub show_check: bool,
+    // shades every square controlled by this side, taken from the control bitboards
+    pub attacked_by: Option<PieceColor>
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            flipped: false,
+            coordinates: true,
+            last_move: None,
+            highlights: Vec::new(),
+            arrows: Vec::new(),
+            show_check: true,
+            attacked_by: None
+        }
+    }
+}
+
+fn ascii_char(piece: &Piece) -> char {
+    let c = match piece.piece_type {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k'
+    };
+
+    if piece.color == PieceColor::White { c.to_ascii_uppercase() } else { c }
+}
+
+fn unicode_char(piece: &Piece) -> char {
+    match (piece.color, piece.piece_type) {
+        (PieceColor::White, PieceType::Pawn) => '♙',
+        (PieceColor::White, PieceType::Knight) => '♘',
+        (PieceColor::White, PieceType::Bishop) => '♗',
+        (PieceColor::White, PieceType::Rook) => '♖',
+        (PieceColor::White, PieceType::Queen) => '♕',
+        (PieceColor::White, PieceType::King) => '♔',
+        (PieceColor::Black, PieceType::Pawn) => '♟',
+        (PieceColor::Black, PieceType::Knight) => '♞',
+        (PieceColor::Black, PieceType::Bishop) => '♝',
+        (PieceColor::Black, PieceType::Rook) => '♜',
+        (PieceColor::Black, PieceType::Queen) => '♛',
+        (PieceColor::Black, PieceType::King) => '♚'
+    }
+}
+
+// rank 8 at the top for white, rank 1 at the top for black
+fn diagram(perspective: PieceColor, square: impl Fn(Position) -> char) -> String {
+    let flipped = perspective == PieceColor::Black;
+    let mut out = String::new();
+
+    for row in 0..8 {
+        let y = if flipped { 7 - row } else { row };
+        out.push_str(&format!("{} ", 8 - y));
+
+        for column in 0..8 {
+            let x = if flipped { 7 - column } else { column };
+            out.push(square(Position { x, y }));
+            out.push(' ');
+        }
+
+        out.pop();
+        out.push('\n');
+    }
+
+    out.push_str("  ");
+    for column in 0..8 {
+        let x = if flipped { 7 - column } else { column };
+        out.push((b'a' + x as u8) as char);
+        out.push(' ');
+    }
+
+    out.pop();
+    out.push('\n');
+    out
+}
+
+pub fn ascii(board: &Board) -> String {
+    diagram(PieceColor::White, |pos| {
+        board.get_piece_at(pos.y, pos.x).map_or('.', |piece| ascii_char(&piece))
+    })
+}
+
+pub fn unicode(board: &Board, perspective: PieceColor) -> String {
+    diagram(perspective, |pos| {
+        board.get_piece_at(pos.y, pos.x).map_or('·', |piece| unicode_char(&piece))
+    })
+}
+
+pub fn bitboard(mask: u64) -> String {
+    diagram(PieceColor::White, |pos| {
+        if mask & pos.to_bitboard() != 0 { '1' } else { '.' }
+    })
+}
+
+fn square_origin(pos: Position, options: &SvgOptions) -> (usize, usize) {
+    let (column, row) = if options.flipped { (7 - pos.x, 7 - pos.y) } else { (pos.x, pos.y) };
+    let margin = if options.coordinates { MARGIN } else { 0 };
+
+    (margin + column * SQUARE_SIZE, row * SQUARE_SIZE)
+}
+
+fn square_center(pos: Position, options: &SvgOptions) -> (usize, usize) {
+    let (x, y) = square_origin(pos, options);
+    (x + SQUARE_SIZE / 2, y + SQUARE_SIZE / 2)
+}
+
+fn fill_square(out: &mut String, pos: Position, color: &str, opacity: f64, options: &SvgOptions) {
+    let (x, y) = square_origin(pos, options);
+    let _ = writeln!(out, r#"<rect x="{x}" y="{y}" width="{SQUARE_SIZE}" height="{SQUARE_SIZE}" fill="{color}" fill-opacity="{opacity}"/>"#);
+}
+
+fn squares(mask: u64) -> impl Iterator<Item = Position> {
+    (0..64).filter(move |i| mask & (1u64 << i) != 0).map(|i| Position { x: i % 8, y: i / 8 })
+}
+
+pub fn svg(board: &Board, options: &SvgOptions) -> String {
+    let margin = if options.coordinates { MARGIN } else { 0 };
+    let width = margin + 8 * SQUARE_SIZE;
+    let height = 8 * SQUARE_SIZE + margin;
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#);
+    let _ = writeln!(out, r#"<defs><marker id="arrowhead" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z" fill="{ARROW}"/></marker></defs>"#);
+
+    for pos in squares(!0) {
+        let color = if (pos.x + pos.y) % 2 == 0 { LIGHT_SQUARE } else { DARK_SQUARE };
+        fill_square(&mut out, pos, color, 1.0, options);
+    }
+
+    if let Some(color) = options.attacked_by {
+        let control = match color {
+            PieceColor::White => board.control_bitboards.white_control,
+            PieceColor::Black => board.control_bitboards.black_control
+        };
+
+        for pos in squares(control) {
+            fill_square(&mut out, pos, ATTACKED, 0.35, options);
+        }
+    }
+
+    if let Some((from, to)) = options.last_move {
+        fill_square(&mut out, from, LAST_MOVE, 0.8, options);
+        fill_square(&mut out, to, LAST_MOVE, 0.8, options);
+    }
+
+    for pos in &options.highlights {
+        fill_square(&mut out, *pos, HIGHLIGHT, 0.6, options);
+    }
+
+    // CheckInfo::checked holds the square of the king in check
+    if options.show_check {
+        for pos in squares(board.white_check.checked | board.black_check.checked) {
+            let (x, y) = square_center(pos, options);
+            let _ = writeln!(out, r#"<circle cx="{x}" cy="{y}" r="{}" fill="{CHECK}" fill-opacity="0.8"/>"#, SQUARE_SIZE / 2);
+        }
+    }
+
+    if options.coordinates {
+        for i in 0..8 {
+            let file = Position { x: i, y: 7 };
+            let (x, _) = square_center(file, options);
+            let _ = writeln!(out, r#"<text x="{x}" y="{}" font-size="14" font-family="sans-serif" text-anchor="middle">{}</text>"#, 8 * SQUARE_SIZE + 15, (b'a' + i as u8) as char);
+
+            let rank = Position { x: 0, y: i };
+            let (_, y) = square_center(rank, options);
+            let _ = writeln!(out, r#"<text x="{}" y="{}" font-size="14" font-family="sans-serif" text-anchor="middle">{}</text>"#, MARGIN / 2, y + 5, 8 - i);
+        }
+    }
+
+    for piece in board.pieces() {
+        let (x, y) = square_center(piece.pos, options);
+        let _ = writeln!(out, r#"<text x="{x}" y="{}" font-size="38" text-anchor="middle" dominant-baseline="central">{}</text>"#, y + 2, unicode_char(piece));
+    }
+
+    for (from, to) in &options.arrows {
+        let (x1, y1) = square_center(*from, options);
+        let (x2, y2) = square_center(*to, options);
+        let _ = writeln!(out, r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{ARROW}" stroke-width="8" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#);
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
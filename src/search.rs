@@ -1,16 +1,19 @@
-use crate::r#const::{CASTLING_VALUE, CHECK_VALUE, DEFAULT_MARGIN, KILLER_MOVE_VALUE, MAX_WINDOW_WIDTH, PAWN_DEVELOPMENT_BONUS, PROMOTION_VALUE, PV_MOVE};
+use crate::r#const::{CAPTURE_VALUE, CASTLING_VALUE, CHECK_VALUE, DEFAULT_MARGIN, KILLER_MOVE_VALUE, MAX_MOVES, MAX_WINDOW_WIDTH, PAWN_DEVELOPMENT_BONUS, PROMOTION_VALUE, PV_MOVE};
 use crate::evaluation::{evaluate, EvaluationResult};
 use crate::board::{Board, ResultType};
-use crate::moves::{Move, MoveType};
+use crate::movegen::MovePicker;
+use crate::moves::{Move, MoveList, PackedMove};
 use crate::piece::PieceType;
 use core::f64;
+use serde::Serialize;
 use std::collections::HashMap;
 
 pub struct Minimax {
     evaluation_cache: EvalCache,
-    move_evaluation_cache: HashMap<usize, f64>,
+    // scores depend on the position (SEE, the hash move), so they're keyed by it too
+    move_evaluation_cache: HashMap<(u64, PackedMove), f64>,
     transposition_table: TranspositionTable,
-    killer_moves: Vec<Vec<Option<Move>>>,
+    killer_moves: Vec<Vec<Option<PackedMove>>>,
     pub nodes: u64,
     is_stopping: bool,
 }
@@ -22,18 +25,18 @@ pub enum NodeType {
     All
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct Node {
     depth: u8,
     node_type: NodeType,
     score: f64,
-    best_move: Option<Move>
+    best_move: Option<PackedMove>
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SearchResult {
     pub value: f64,
-    pub moves: Vec<Move>
+    pub moves: Vec<PackedMove>
 }
 
 pub struct TranspositionTable {
@@ -51,7 +54,7 @@ impl TranspositionTable {
         }
     }
 
-    pub fn store(&mut self, hash: i64, node: Node) {
+    pub fn store(&mut self, hash: u64, node: Node) {
         let index = (hash as usize) & self.mask;
         if let Some(entry) = &self.entries[index] {
             if entry.depth <= node.depth {
@@ -62,20 +65,20 @@ impl TranspositionTable {
         }
     }
 
-    pub fn get(&self, hash: i64) -> &Option<Node> {
+    pub fn get(&self, hash: u64) -> &Option<Node> {
         let index = (hash as usize) & self.mask;
         &self.entries[index]
     }
 }
 
 pub struct EvalCache {
-    entries: Vec<(i64, EvaluationResult)>,  // (hash, result)
+    entries: Vec<(u64, EvaluationResult)>,  // (hash, result)
     mask: usize
 }
 
 impl EvalCache {
     pub fn new(size_mb: usize) -> Self {
-        let num_entries = (size_mb * 1024 * 1024) / std::mem::size_of::<(i64, EvaluationResult)>();
+        let num_entries = (size_mb * 1024 * 1024) / std::mem::size_of::<(u64, EvaluationResult)>();
         let size = num_entries.next_power_of_two();
         EvalCache { 
             entries: vec![(0, EvaluationResult::default()); size], 
@@ -83,7 +86,7 @@ impl EvalCache {
         }
     }
     
-    pub fn get(&self, hash: i64) -> Option<&EvaluationResult> {
+    pub fn get(&self, hash: u64) -> Option<&EvaluationResult> {
         let index = (hash as usize) & self.mask;
         let (stored_hash, result) = &self.entries[index];
         if *stored_hash == hash {
@@ -93,12 +96,12 @@ impl EvalCache {
         }
     }
     
-    pub fn store(&mut self, hash: i64, result: EvaluationResult) {
+    pub fn store(&mut self, hash: u64, result: EvaluationResult) {
         let index = (hash as usize) & self.mask;
         self.entries[index] = (hash, result);
     }
 
-    pub fn contains(&self, hash: i64) -> bool {
+    pub fn contains(&self, hash: u64) -> bool {
         let index = (hash as usize) & self.mask;
         self.entries[index].0 == hash
     }
@@ -116,7 +119,7 @@ impl Minimax {
         }
     }
 
-    pub fn store_position(&mut self, board: &Board, depth: u8, node_type: NodeType, score: f64, best_move: Option<Move>) {
+    pub fn store_position(&mut self, board: &Board, depth: u8, node_type: NodeType, score: f64, best_move: Option<PackedMove>) {
         let node = Node {
             depth,
             node_type,
@@ -127,13 +130,13 @@ impl Minimax {
         self.transposition_table.store(board.hash, node);
     }
 
-    pub fn check_position(&self, board: &Board, depth: u8, alpha: f64, beta: f64) -> Option<(f64, Option<Move>)> {
+    pub fn check_position(&self, board: &Board, depth: u8, alpha: f64, beta: f64) -> Option<(f64, Option<PackedMove>)> {
         if let Some(node) = self.transposition_table.get(board.hash) {
             if node.depth >= depth {
                 match node.node_type {
-                    NodeType::PV => return Some((node.score, node.best_move.clone())),
-                    NodeType::Cut if node.score >= beta => return Some((beta, node.best_move.clone())),
-                    NodeType::All if node.score <= alpha => return Some((alpha, node.best_move.clone())),
+                    NodeType::PV => return Some((node.score, node.best_move)),
+                    NodeType::Cut if node.score >= beta => return Some((beta, node.best_move)),
+                    NodeType::All if node.score <= alpha => return Some((alpha, node.best_move)),
                     _ => {}
                 }
             }
@@ -142,13 +145,21 @@ impl Minimax {
         None
     }
 
-    pub fn store_killer_move(&mut self, m: &Move, depth: u8) {
-        let first_killer = &self.killer_moves[depth as usize][0];
+    // the staged picker for a full-width node, seeded with the hash move and this depth's killers
+    fn move_picker(&self, board: &Board, depth: u8) -> MovePicker {
+        let hash_move = self.transposition_table.get(board.hash).and_then(|node| node.best_move);
+        let killers = &self.killer_moves[depth as usize];
+
+        MovePicker::with_hints(board, hash_move, [killers[0], killers[1]])
+    }
+
+    pub fn store_killer_move(&mut self, m: PackedMove, depth: u8) {
+        let first_killer = self.killer_moves[depth as usize][0];
 
         if let Some(killer) = first_killer {
             if killer != m {
-                self.killer_moves[depth as usize][1] = Some(killer.clone());
-                self.killer_moves[depth as usize][0] = Some(m.clone());
+                self.killer_moves[depth as usize][1] = Some(killer);
+                self.killer_moves[depth as usize][0] = Some(m);
             }
         }
     }
@@ -162,21 +173,22 @@ impl Minimax {
             println!("Move {}: {:?} color: {:?} from: {:?} to: {:?}", 
                      i+1, m, m.piece_color, m.from, m.to);
             
-            let legal_moves = temp_board.get_total_legal_moves(None);
+            let mut legal_moves = temp_board.generate_moves(None, false);
 
             let move_exists = legal_moves.iter().any(|legal_m| 
-                legal_m.from == m.from && legal_m.to == m.to);
+                legal_m.from() == m.from && legal_m.to() == m.to);
             
             if !move_exists {
                 println!("ERROR: Move is not legal in current position!");
                 println!("Legal moves are:");
                 for legal_m in &legal_moves {
-                    println!("{:?} from {:?} to {:?}", legal_m, legal_m.from, legal_m.to);
+                    println!("{:?} from {:?} to {:?}", legal_m, legal_m.from(), legal_m.to());
                 }
                 break;
             }
             
-            println!("Best moves: {:?}", self.sort(legal_moves, &mut temp_board, start_depth - i as u8));
+            self.sort(&mut legal_moves, &mut temp_board, start_depth - i as u8);
+            println!("Best moves: {:?}", legal_moves);
             println!("King moves: {:?}", temp_board.get_legal_moves(temp_board.get_king(board.turn).unwrap().index));
             temp_board.make_move(m);
             println!("Board after move\n {:?}", temp_board);
@@ -309,18 +321,23 @@ impl Minimax {
 
         if maximizer {
             let mut value = f64::NEG_INFINITY;
-            let mut moves: Vec<Move> = vec![];
+            let mut moves: Vec<PackedMove> = vec![];
             let mut best_move = None;
             let mut node_type = NodeType::All;
 
-            let legal_moves = self.sort(board.get_total_legal_moves(None), board, depth);
+            let mut picker = self.move_picker(board, depth);
 
-            for (i, m) in legal_moves.iter().enumerate() {
-                let history = board.make_move(m);
+            for i in 0.. {
+                let Some(m) = picker.next(board, |moves, board| self.sort(moves, board, depth)) else {
+                    break;
+                };
+
+                let rich = board.unpack(m);
+                let history = board.make_move(&rich);
 
                 let new_depth = if i >= 3 && depth >= 3
-                    && !m.move_type.contains(&MoveType::Capture)
-                    && !m.move_type.contains(&MoveType::Check) {
+                    && !m.is_capture()
+                    && !m.is_check() {
                     depth - 1 - (i / 6).min(2) as u8
                 } else {
                     depth - 1
@@ -333,21 +350,21 @@ impl Minimax {
                     result = self.search(board, depth - 1, alpha, beta, !maximizer);
                 }
 
-                board.unmake_move(m, &history);
+                board.unmake_move(&rich, &history);
                 if start_hash != board.hash {
                     println!("POS CORRUPTED AT DEPTH {depth}");
                 }
 
                 if result.value > value {
                     value = result.value;
-                    best_move = Some(m.clone());
+                    best_move = Some(m);
 
                     if !result.moves.is_empty() {
-                        let mut new_moves = vec![m.clone()];
+                        let mut new_moves = vec![m];
                         new_moves.extend(result.moves);
                         moves = new_moves;
                     } else {
-                        moves = vec![m.clone()]
+                        moves = vec![m]
                     }
                 }
 
@@ -376,32 +393,33 @@ impl Minimax {
             }
         } else {
             let mut value = f64::INFINITY;
-            let mut moves: Vec<Move> = vec![];
+            let mut moves: Vec<PackedMove> = vec![];
             let mut best_move = None;
             let mut node_type = NodeType::All;
             
-            let legal_moves = self.sort(board.get_total_legal_moves(None), board, depth);
-            
-            for m in &legal_moves {
-                let history = board.make_move(m);
+            let mut picker = self.move_picker(board, depth);
+
+            while let Some(m) = picker.next(board, |moves, board| self.sort(moves, board, depth)) {
+                let rich = board.unpack(m);
+                let history = board.make_move(&rich);
 
                 let result = self.search(board, depth - 1, alpha, beta, true);
 
-                board.unmake_move(m, &history);
+                board.unmake_move(&rich, &history);
                 if start_hash != board.hash {
                     println!("POS CORRUPTED AT DEPTH {depth}");
                 }
 
                 if result.value < value {
                     value = result.value;
-                    best_move = Some(m.clone());
+                    best_move = Some(m);
 
                     if !result.moves.is_empty() {
-                        let mut new_moves = vec![m.clone()];
+                        let mut new_moves = vec![m];
                         new_moves.extend(result.moves);
                         moves = new_moves;
                     } else {
-                        moves = vec![m.clone()]
+                        moves = vec![m]
                     }
                 }
 
@@ -434,20 +452,33 @@ impl Minimax {
     pub fn quiescence(&mut self, board: &mut Board, mut alpha: f64, mut beta: f64, maximizer: bool, depth: i8) -> f64 {
         self.nodes += 1;
 
+        // a side in check can't stand pat, it has to find an evasion, quiet ones included
+        let check = board.get_check(board.turn);
+        let in_check = check.checked != 0 || check.double_checked != 0;
         let stand_pat = self.evaluate(board).to_value();
 
         if maximizer {
-            if stand_pat >= beta {
-                return beta;
-            }
-            if stand_pat > alpha {
-                alpha = stand_pat;
+            if !in_check {
+                if stand_pat >= beta {
+                    return beta;
+                }
+                if stand_pat > alpha {
+                    alpha = stand_pat;
+                }
             }
 
-            let captures = board.get_total_legal_moves_quiescence(None, true);
-            let sorted = self.sort(captures, board, 0);
+            let mut picker = MovePicker::new(board, !in_check);
+            let mut has_moves = false;
+
+            while let Some(packed) = picker.next(board, |moves, board| self.sort(moves, board, 0)) {
+                let m = board.unpack(packed);
+                has_moves = true;
+
+                // a capture that loses material can't do better than standing pat
+                if !in_check && packed.is_capture() && !board.see_ge(&m, 0) {
+                    continue;
+                }
 
-            for m in sorted {
                 let history = board.make_move(&m);
                 let score = self.quiescence(board, alpha, beta, false, depth - 1);
                 board.unmake_move(&m, &history);
@@ -460,19 +491,34 @@ impl Minimax {
                 }
             }
 
+            // no evasion means mate, which the evaluation already scored
+            if in_check && !has_moves {
+                return stand_pat;
+            }
+
             alpha
         } else {
-            if stand_pat <= alpha {
-                return alpha;
-            }
-            if stand_pat < beta {
-                beta = stand_pat;
+            if !in_check {
+                if stand_pat <= alpha {
+                    return alpha;
+                }
+                if stand_pat < beta {
+                    beta = stand_pat;
+                }
             }
 
-            let captures = board.get_total_legal_moves_quiescence(None, true);
-            let sorted = self.sort(captures, board, 0);
+            let mut picker = MovePicker::new(board, !in_check);
+            let mut has_moves = false;
+
+            while let Some(packed) = picker.next(board, |moves, board| self.sort(moves, board, 0)) {
+                let m = board.unpack(packed);
+                has_moves = true;
+
+                // a capture that loses material can't do better than standing pat
+                if !in_check && packed.is_capture() && !board.see_ge(&m, 0) {
+                    continue;
+                }
 
-            for m in sorted {
                 let history = board.make_move(&m);
                 let score = self.quiescence(board, alpha, beta, true, depth - 1);
                 board.unmake_move(&m, &history);
@@ -485,6 +531,10 @@ impl Minimax {
                 }
             }
 
+            if in_check && !has_moves {
+                return stand_pat;
+            }
+
             beta
         }
     }
@@ -499,30 +549,34 @@ impl Minimax {
         value
     }
 
-    pub fn evaluate_move_base(m: &Move, board: &mut Board) -> f64 {
+    pub fn evaluate_move_base(m: PackedMove, board: &mut Board) -> f64 {
         let mut value = 0.0;
 
-        value += m.mvv_lva();
+        // winning and even exchanges go ahead of the quiet moves, losing ones fall behind them
+        if m.is_capture() {
+            let see = board.see(&board.unpack(m)) as f64;
+            value += if see >= 0.0 { CAPTURE_VALUE + see } else { see };
+        }
 
-        if m.move_type.contains(&MoveType::Promotion) {
+        if m.is_promotion() {
             value += PROMOTION_VALUE;
         }
 
-        if m.move_type.contains(&MoveType::Check) {
+        if m.is_check() {
             value += CHECK_VALUE;
         }
 
-        if m.move_type.contains(&MoveType::Castling) {
+        if m.is_castling() {
             value += CASTLING_VALUE;
         }
 
         value += m.ps_table(board);
 
-        if board.moves < 10 && m.piece_type == PieceType::Pawn {
+        if board.moves < 10 && m.piece_type() == PieceType::Pawn {
             value += PAWN_DEVELOPMENT_BONUS;
 
-            let file = m.to.x;
-            let rank = m.to.y;
+            let file = m.to().x;
+            let rank = m.to().y;
 
             if (file == 3 || file == 4) && (rank >= 2 && rank <= 5) {
                 value += 200.0;
@@ -530,7 +584,7 @@ impl Minimax {
                 value += 50.0;
             }
 
-            if (m.from.y as isize - rank as isize).abs() == 2 {
+            if (m.from().y as isize - rank as isize).abs() == 2 {
                 value += PAWN_DEVELOPMENT_BONUS;
             }
         }
@@ -538,56 +592,53 @@ impl Minimax {
         value
     }
 
-    pub fn evaluate_move(&mut self, m: &Move, board: &mut Board, depth: u8) -> f64 {
-        if self.move_evaluation_cache.contains_key(&m.hash()) {
-            return *self.move_evaluation_cache.get(&m.hash()).unwrap()
+    pub fn evaluate_move(&mut self, m: PackedMove, board: &mut Board, depth: u8) -> f64 {
+        if let Some(&value) = self.move_evaluation_cache.get(&(board.hash, m)) {
+            return value
         }
         let mut value = Minimax::evaluate_move_base(m, board);
 
         if let Some(node) = self.transposition_table.get(board.hash) {
-            if let Some(best_move) = &node.best_move {
-                if best_move == m {
-                    value += PV_MOVE;
-                }
+            if node.best_move == Some(m) {
+                value += PV_MOVE;
             }
         }
 
-        if !m.move_type.contains(&MoveType::Capture) {
-            if let Some(killer) = &self.killer_moves[depth as usize][0] {
-                if m == killer {
-                    value += KILLER_MOVE_VALUE;
-                }
+        if !m.is_capture() {
+            if self.killer_moves[depth as usize][0] == Some(m) {
+                value += KILLER_MOVE_VALUE;
             }
 
-            if let Some(killer) = &self.killer_moves[depth as usize][1] {
-                if m == killer {
-                    value += KILLER_MOVE_VALUE - 1000.0;
-                }
+            if self.killer_moves[depth as usize][1] == Some(m) {
+                value += KILLER_MOVE_VALUE - 1000.0;
             }
         }
 
-        self.move_evaluation_cache.insert(m.hash(), value);
+        self.move_evaluation_cache.insert((board.hash, m), value);
 
         value
     }
 
-    pub fn sort(&mut self, moves: Vec<Move>, board: &mut Board, depth: u8) -> Vec<Move> {
-        let scores = moves.iter()
-            .map(|m| self.evaluate_move(m, board, depth));
-        
-        let mut indices: Vec<(usize, f64)> = scores
-            .enumerate()
-            .map(|(i, score)| (i, score))
-            .collect();
+    // orders the list in place, best first; stable so equal scores keep generation order
+    pub fn sort(&mut self, moves: &mut MoveList, board: &mut Board, depth: u8) {
+        let mut scores = [0.0; MAX_MOVES];
+
+        for (i, &m) in moves.iter().enumerate() {
+            scores[i] = self.evaluate_move(m, board, depth);
+        }
 
-        indices.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        for i in 1..moves.len() {
+            let (m, score) = (moves[i], scores[i]);
+            let mut j = i;
 
-        let mut result: Vec<Move> = Vec::with_capacity(moves.len());
+            while j > 0 && scores[j - 1] < score {
+                moves[j] = moves[j - 1];
+                scores[j] = scores[j - 1];
+                j -= 1;
+            }
 
-        for (i, _) in indices {
-            result.push(moves[i].clone());
+            moves[j] = m;
+            scores[j] = score;
         }
-        
-        result
     }
-}
\ No newline at end of file
+}
@@ -3,8 +3,8 @@ use axum::{
     routing::get,
     Router,
     response::IntoResponse,
-    http::StatusCode,
-    Json, extract::State,
+    http::{header, StatusCode},
+    Json, extract::{Query, State},
     routing::post,
 };
 use serde::{Deserialize, Serialize};
@@ -15,7 +15,12 @@ use std::collections::HashMap;
 use std::env;
 use dotenv::dotenv;
 
+use crate::board::{Board, ResultType};
+use crate::moves::Move;
+use crate::piece::PieceColor;
 use crate::protocol::UciProtocol;
+use crate::render::{self, SvgOptions};
+use crate::threats::{self, Threats};
 
 struct AppState {
     protocols: Mutex<HashMap<String, UciProtocol>>,
@@ -33,6 +38,52 @@ struct UciResponse {
     client_id: String,
     response: Vec<String>
 }
+
+#[derive(Deserialize)]
+struct SvgQuery {
+    client_id: Option<String>,
+    fen: Option<String>,
+    flipped: Option<bool>,
+    attacks: Option<String>
+}
+
+#[derive(Deserialize)]
+struct MovesRequest {
+    fen: Option<String>,
+    // played from the FEN in UCI notation before the state is read
+    #[serde(default)]
+    moves: Vec<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct LegalMove {
+    pub uci: String,
+    pub san: String,
+    // the position once the move is played
+    pub fen: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct GameState {
+    pub fen: String,
+    pub turn: PieceColor,
+    pub check: bool,
+    pub result: ResultType,
+    pub moves: Vec<LegalMove>
+}
+
+#[derive(Deserialize)]
+struct ThreatsQuery {
+    client_id: Option<String>,
+    fen: Option<String>,
+    color: Option<String>
+}
+
+#[derive(Deserialize)]
+struct PgnQuery {
+    client_id: String
+}
+
 struct ResponseWriter {
     lines: Vec<String>,
     buffer: String,
@@ -222,6 +273,7 @@ async fn process_command(state: &Arc<AppState>, client_id: &str, command: &str)
             responses.push("id author ggod".to_string());
             responses.push("option name EngineType type combo default Minimax var Minimax var MCTS".to_string());
             responses.push("option name EnableBook type check default false".to_string());
+            responses.push("option name UCI_Chess960 type check default false".to_string());
             responses.push("uciok".to_string());
             return responses;
         },
@@ -273,6 +325,17 @@ async fn process_command(state: &Arc<AppState>, client_id: &str, command: &str)
 
             return writer.get_messages();
         },
+        "d" => {
+            let mut writer = ResponseWriter::new();
+
+            if let Err(e) = protocol.display(&mut writer) {
+                return vec![format!("info string Error executing d command: {}", e)];
+            }
+
+            writer.flush().unwrap();
+
+            return writer.get_messages();
+        },
         "quit" => {
             return vec!["Disconnecting".to_string()];
         },
@@ -291,6 +354,126 @@ async fn command(State(state): State<Arc<AppState>>, Json(request): Json<UciRequ
     }))
 }
 
+// renders a FEN, or the current position of a client with its last move, as an SVG diagram
+async fn svg(State(state): State<Arc<AppState>>, Query(query): Query<SvgQuery>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let flipped = query.flipped.unwrap_or(false);
+    let attacked_by = match query.attacks.as_deref() {
+        Some("white") => Some(PieceColor::White),
+        Some("black") => Some(PieceColor::Black),
+        Some(a) => return Err((StatusCode::BAD_REQUEST, format!("Unknown side to show attacks for: {}", a))),
+        None => None
+    };
+
+    let svg = if let Some(fen) = query.fen {
+        let board = Board::try_from_fen(&fen).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid FEN {}: {}", fen, e)))?;
+        render::svg(&board, &SvgOptions { flipped, attacked_by, ..SvgOptions::default() })
+    } else if let Some(client_id) = query.client_id {
+        let protocols = match state.protocols.lock() {
+            Ok(p) => p,
+            Err(e) => e.into_inner(),
+        };
+
+        match protocols.get(&client_id) {
+            Some(protocol) => protocol.to_svg(flipped, attacked_by),
+            None => return Err((StatusCode::NOT_FOUND, format!("Unknown client {}", client_id)))
+        }
+    } else {
+        render::svg(&Board::startpos(), &SvgOptions { flipped, attacked_by, ..SvgOptions::default() })
+    };
+
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg))
+}
+
+async fn moves(Json(request): Json<MovesRequest>) -> Result<Json<GameState>, (StatusCode, String)> {
+    game_state(request.fen.as_deref(), &request.moves)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+// the legal moves and the state of a FEN, or of the position after a line played from it, so
+// clients don't have to validate moves themselves
+pub fn game_state(fen: Option<&str>, moves: &[String]) -> Result<GameState, String> {
+    let mut board = match fen {
+        Some(fen) => Board::try_from_fen(fen).map_err(|e| format!("Invalid FEN {}: {}", fen, e))?,
+        None => Board::startpos()
+    };
+
+    for uci in moves {
+        let m = Move::from_uci(uci, &mut board).map_err(|e| format!("Invalid move {}: {}", uci, e))?;
+        board.make_move(&m);
+    }
+
+    Ok(board_state(&mut board))
+}
+
+fn board_state(board: &mut Board) -> GameState {
+    let check = board.get_check(board.turn);
+    let in_check = check.checked != 0 || check.double_checked != 0;
+
+    let moves = board.get_total_legal_moves(None).into_iter()
+        .map(|m| {
+            let uci = if board.chess960 { m.to_uci_chess960() } else { m.to_uci() };
+            let san = m.to_san(board);
+
+            let history = board.make_move(&m);
+            let fen = board.to_fen();
+            board.unmake_move(&m, &history);
+
+            LegalMove { uci, san, fen }
+        })
+        .collect();
+
+    GameState {
+        fen: board.to_fen(),
+        turn: board.turn,
+        check: in_check,
+        result: board.get_result(),
+        moves
+    }
+}
+
+// the threats against one side of a FEN or of a client's current position, the side to move
+// unless `color` says otherwise
+async fn threats(State(state): State<Arc<AppState>>, Query(query): Query<ThreatsQuery>) -> Result<Json<Threats>, (StatusCode, String)> {
+    let color = match query.color.as_deref() {
+        Some("white") => Some(PieceColor::White),
+        Some("black") => Some(PieceColor::Black),
+        Some(c) => return Err((StatusCode::BAD_REQUEST, format!("Unknown side to show threats for: {}", c))),
+        None => None
+    };
+
+    let board = if let Some(fen) = query.fen {
+        Board::try_from_fen(&fen).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid FEN {}: {}", fen, e)))?
+    } else if let Some(client_id) = query.client_id {
+        let protocols = match state.protocols.lock() {
+            Ok(p) => p,
+            Err(e) => e.into_inner(),
+        };
+
+        match protocols.get(&client_id) {
+            Some(protocol) => protocol.board().clone(),
+            None => return Err((StatusCode::NOT_FOUND, format!("Unknown client {}", client_id)))
+        }
+    } else {
+        Board::startpos()
+    };
+
+    Ok(Json(threats::threats(&board, color.unwrap_or(board.turn))))
+}
+
+// exports the game a client has played so far
+async fn pgn(State(state): State<Arc<AppState>>, Query(query): Query<PgnQuery>) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let protocols = match state.protocols.lock() {
+        Ok(p) => p,
+        Err(e) => e.into_inner(),
+    };
+
+    match protocols.get(&query.client_id) {
+        Some(protocol) => Ok(([(header::CONTENT_TYPE, "application/x-chess-pgn")], protocol.to_pgn())),
+        None => Err((StatusCode::NOT_FOUND, format!("Unknown client {}", query.client_id)))
+    }
+}
+
 pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
 
@@ -315,6 +498,10 @@ pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
     let app = Router::new()
         .route("/ws", get(websocket_handler))
         .route("/uci", post(command))
+        .route("/moves", post(moves))
+        .route("/svg", get(svg))
+        .route("/pgn", get(pgn))
+        .route("/threats", get(threats))
         .with_state(state);
 
     let host = env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
@@ -0,0 +1,181 @@
+use serde::Serialize;
+
+use crate::board::Board;
+use crate::moves::{Position, Vector};
+use crate::piece::{Piece, PieceColor, PieceType};
+use crate::r#const::PIECE_VALUES;
+
+// read-only views of the control tables, shaped for clients drawing arrows and highlights over
+// the board. everything serializes with squares in algebraic notation
+
+// the pieces fighting over a square. defenders have the color of the piece standing on it, or of
+// the side to move when it's empty
+#[derive(Debug, Clone, Serialize)]
+pub struct SquareControl {
+    pub square: Position,
+    pub piece: Option<Piece>,
+    pub attackers: Vec<Piece>,
+    pub defenders: Vec<Piece>
+}
+
+// a piece that can be taken for free, or by something worth less than itself
+#[derive(Debug, Clone, Serialize)]
+pub struct HangingPiece {
+    pub piece: Piece,
+    pub attackers: Vec<Piece>,
+    pub defenders: Vec<Piece>
+}
+
+// a piece that can't leave the line between an enemy slider and the piece behind it. the pin is
+// absolute when that piece is the king, otherwise moving away only loses material
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedPiece {
+    pub piece: Piece,
+    pub pinner: Piece,
+    pub target: Piece,
+    pub absolute: bool
+}
+
+// moving `piece` off the line uncovers an attack of `slider` on `target`
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredAttack {
+    pub piece: Piece,
+    pub slider: Piece,
+    pub target: Piece
+}
+
+// everything threatening one side at once: its hanging and pinned pieces, the squares its king
+// can't step to and the discovered attacks the other side has lined up
+#[derive(Debug, Clone, Serialize)]
+pub struct Threats {
+    pub color: PieceColor,
+    pub hanging: Vec<HangingPiece>,
+    pub pins: Vec<PinnedPiece>,
+    pub king_danger: Vec<Position>,
+    pub discovered: Vec<DiscoveredAttack>
+}
+
+const STRAIGHT: [Vector; 4] = [Vector { x: 1, y: 0 }, Vector { x: -1, y: 0 }, Vector { x: 0, y: 1 }, Vector { x: 0, y: -1 }];
+const DIAGONAL: [Vector; 4] = [Vector { x: 1, y: 1 }, Vector { x: 1, y: -1 }, Vector { x: -1, y: 1 }, Vector { x: -1, y: -1 }];
+const ALL: [Vector; 8] = [
+    Vector { x: 1, y: 0 }, Vector { x: -1, y: 0 }, Vector { x: 0, y: 1 }, Vector { x: 0, y: -1 },
+    Vector { x: 1, y: 1 }, Vector { x: 1, y: -1 }, Vector { x: -1, y: 1 }, Vector { x: -1, y: -1 }
+];
+
+pub fn threats(board: &Board, color: PieceColor) -> Threats {
+    Threats {
+        color,
+        hanging: hanging_pieces(board, color),
+        pins: pinned_pieces(board, color),
+        king_danger: king_danger_squares(board, color),
+        discovered: discovered_attacks(board, color.opposite())
+    }
+}
+
+pub fn square_control(board: &Board, pos: Position) -> SquareControl {
+    let piece = board.get_piece_at(pos.y, pos.x);
+    let color = piece.map_or(board.turn, |piece| piece.color);
+
+    SquareControl {
+        square: pos,
+        piece,
+        attackers: attackers_of(board, pos, color.opposite()),
+        defenders: attackers_of(board, pos, color)
+    }
+}
+
+// pieces of the color that attack the square, or defend it when their own piece stands there,
+// in piece index order. x-rays through the enemy king aren't counted, pawn pushes neither
+pub fn attackers_of(board: &Board, pos: Position, color: PieceColor) -> Vec<Piece> {
+    board.get_control_at(pos.y, pos.x, Some(color), true).iter()
+        .filter(|entry| !entry.obscured)
+        .filter_map(|entry| board.get_piece(entry.index).copied())
+        .collect()
+}
+
+// kings never hang, being attacked is a check
+pub fn hanging_pieces(board: &Board, color: PieceColor) -> Vec<HangingPiece> {
+    board.pieces()
+        .filter(|piece| piece.color == color && piece.piece_type != PieceType::King)
+        .filter_map(|&piece| {
+            let attackers = attackers_of(board, piece.pos, color.opposite());
+            let defenders = attackers_of(board, piece.pos, color);
+            let cheapest = attackers.iter().map(|attacker| value(attacker.piece_type)).min()?;
+
+            (defenders.is_empty() || cheapest < value(piece.piece_type)).then_some(HangingPiece { piece, attackers, defenders })
+        })
+        .collect()
+}
+
+// the color's pieces held in place by an enemy bishop, rook or queen
+pub fn pinned_pieces(board: &Board, color: PieceColor) -> Vec<PinnedPiece> {
+    sliders(board, color.opposite())
+        .flat_map(|slider| lines(board, slider).into_iter().map(move |line| (slider, line)))
+        .filter_map(|(pinner, (piece, target))| {
+            let absolute = target.piece_type == PieceType::King;
+            let pinned = piece.color == color && target.color == color && piece.piece_type != PieceType::King &&
+                (absolute || value(target.piece_type) > value(piece.piece_type));
+
+            pinned.then_some(PinnedPiece { piece, pinner, target, absolute })
+        })
+        .collect()
+}
+
+// squares next to the color's king that the other side covers. the king is taken off the board
+// first, so a slider checking along a line still covers the square behind it
+pub fn king_danger_squares(board: &Board, color: PieceColor) -> Vec<Position> {
+    let Some(king) = board.get_king(color) else {
+        return Vec::new();
+    };
+
+    let enemy = board.bb.pieces_of_color(color.opposite());
+    let occupied = board.bb.all_pieces & !king.pos.to_bitboard();
+
+    ALL.iter()
+        .map(|&dir| king.pos.shift(dir))
+        .filter(|square| Board::in_bounds(square.y, square.x))
+        .filter(|&square| board.attackers_to(square, occupied) & enemy != 0)
+        .collect()
+}
+
+// the color's pieces standing between one of its own sliders and an enemy piece
+pub fn discovered_attacks(board: &Board, color: PieceColor) -> Vec<DiscoveredAttack> {
+    sliders(board, color)
+        .flat_map(|slider| lines(board, slider).into_iter().map(move |line| (slider, line)))
+        .filter(|(_, (piece, target))| piece.color == color && target.color != color)
+        .map(|(slider, (piece, target))| DiscoveredAttack { piece, slider, target })
+        .collect()
+}
+
+fn sliders(board: &Board, color: PieceColor) -> impl Iterator<Item = Piece> + '_ {
+    board.pieces()
+        .filter(move |piece| piece.color == color && matches!(piece.piece_type, PieceType::Bishop | PieceType::Rook | PieceType::Queen))
+        .copied()
+}
+
+// the first two pieces along every line the slider moves on
+fn lines(board: &Board, slider: Piece) -> Vec<(Piece, Piece)> {
+    let directions: &[Vector] = match slider.piece_type {
+        PieceType::Bishop => &DIAGONAL,
+        PieceType::Rook => &STRAIGHT,
+        _ => &ALL
+    };
+
+    directions.iter()
+        .filter_map(|&dir| {
+            let mut found = Vec::with_capacity(2);
+            let mut square = slider.pos.shift(dir);
+
+            while found.len() < 2 && Board::in_bounds(square.y, square.x) {
+                found.extend(board.get_piece_at(square.y, square.x));
+                square = square.shift(dir);
+            }
+
+            (found.len() == 2).then(|| (found[0], found[1]))
+        })
+        .collect()
+}
+
+fn value(piece_type: PieceType) -> i32 {
+    PIECE_VALUES[piece_type.index()] as i32
+}
@@ -1,4 +1,5 @@
 use mchess::moves::Position;
+use mchess::render;
 
 pub fn alg(s: &str) -> Position {
     let mut chars = s.chars();
@@ -16,22 +17,5 @@ pub fn alg(s: &str) -> Position {
 }
 
 pub fn show_mask(mask: u64) {
-    print!("  ");
-    for i in 0..8 {
-        print!("{} ", "abcdefgh".chars().nth(i).unwrap());
-    }
-    print!("\n");
-    for rank in 0..8 {
-        print!("{} ", 8 - rank);
-        for file in 0..8 {
-            let pos = Position { x: file, y: rank };
-            let q = mask & pos.to_bitboard();
-            if q == 0 {
-                print!(". ");
-            } else {
-                print!("1 ");
-            }
-        }
-        print!("\n");
-    }
-}
\ No newline at end of file
+    print!("{}", render::bitboard(mask));
+}
@@ -8,14 +8,35 @@ mod piece {
 }
 
 mod special {
+    pub mod attacks;
+    pub mod mailbox;
+    pub mod packed;
+    pub mod pgn;
     pub mod pin;
+    pub mod render;
     pub mod perft;
     pub mod make_unmake;
+    pub mod movegen;
+    pub mod null;
     pub mod control;
     pub mod blocks;
     pub mod evaluation;
     pub mod castling;
     pub mod capture;
+    pub mod chess960;
+    pub mod edit;
+    pub mod fen;
+    pub mod game;
+    pub mod json;
+    pub mod repetition;
+    pub mod result;
+    pub mod san;
+    pub mod see;
+    pub mod server;
+    pub mod threats;
+    pub mod uci;
+    pub mod validate;
+    pub mod zobrist;
     // position-specific tests
     pub mod pos;
 }
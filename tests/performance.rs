@@ -1,6 +1,8 @@
 use std::time::Instant;
 
 use mchess::board::Board;
+use mchess::perft::perft;
+use mchess::pieces::attacks;
 use mchess::search::Minimax;
 
 #[test] 
@@ -26,4 +28,53 @@ fn iterative_deepening_performance() {
     let mut board = Board::from_fen("2k2r2/1ppp4/pn5q/8/8/8/3B1PPP/1Q4K1 w - - 0 1");
 
     chess.iterative_deepening(&mut board, 10, 20000);
-}
\ No newline at end of file
+}
+
+#[test]
+fn perft_performance() {
+    attacks::init();
+
+    for (fen, depth, expected) in [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 4, 197281),
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 3, 97862)
+    ] {
+        let mut board = Board::from_fen(fen);
+
+        let start = Instant::now();
+        let nodes = perft(&mut board, depth);
+        let duration = start.elapsed();
+
+        assert_eq!(nodes, expected, "Perft failed at depth {} for {}", depth, fen);
+        println!("Perft {} depth {}: {} nodes in {:?} ({:.0} nps)", fen, depth, nodes, duration, nodes as f64 / duration.as_secs_f64());
+    }
+}
+
+#[test]
+fn sliding_attacks_performance() {
+    attacks::init();
+
+    let occupancies: Vec<u64> = (0..10000u64)
+        .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) & i.wrapping_mul(0xD1B54A32D192ED03))
+        .collect();
+
+    let start = Instant::now();
+    let mut slow = 0u64;
+    for &occupied in &occupancies {
+        for square in 0..64 {
+            slow ^= attacks::rook_slow(square, occupied) ^ attacks::bishop_slow(square, occupied);
+        }
+    }
+    let slow_duration = start.elapsed();
+
+    let start = Instant::now();
+    let mut magic = 0u64;
+    for &occupied in &occupancies {
+        for square in 0..64 {
+            magic ^= attacks::rook(square, occupied) ^ attacks::bishop(square, occupied);
+        }
+    }
+    let magic_duration = start.elapsed();
+
+    assert_eq!(slow, magic);
+    println!("Ray loops: {:?}, magic lookups: {:?} ({:.1}x)", slow_duration, magic_duration, slow_duration.as_secs_f64() / magic_duration.as_secs_f64());
+}
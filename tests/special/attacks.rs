@@ -0,0 +1,46 @@
+use mchess::piece::PieceColor;
+use mchess::pieces::attacks;
+
+use crate::common::alg;
+
+#[test]
+fn test_sliding_attacks_match_rays() {
+    for i in 0..2000u64 {
+        let occupied = i.wrapping_mul(0x9E3779B97F4A7C15) & i.wrapping_mul(0xD1B54A32D192ED03);
+
+        for square in 0..64 {
+            assert_eq!(attacks::rook(square, occupied), attacks::rook_slow(square, occupied), "Rook attacks differ on {} with {:x}", square, occupied);
+            assert_eq!(attacks::bishop(square, occupied), attacks::bishop_slow(square, occupied), "Bishop attacks differ on {} with {:x}", square, occupied);
+        }
+    }
+}
+
+#[test]
+fn test_sliding_attacks() {
+    let square = |s: &str| alg(s).x + alg(s).y * 8;
+    let bb = |squares: &[&str]| squares.iter().fold(0u64, |acc, s| acc | alg(s).to_bitboard());
+
+    assert_eq!(attacks::rook(square("a1"), 0).count_ones(), 14);
+    assert_eq!(attacks::bishop(square("d4"), 0).count_ones(), 13);
+    assert_eq!(attacks::queen(square("d4"), 0).count_ones(), 27);
+
+    let occupied = bb(&["a3", "c1", "b2"]);
+    assert_eq!(attacks::rook(square("a1"), occupied), bb(&["a2", "a3", "b1", "c1"]));
+    assert_eq!(attacks::bishop(square("a1"), occupied), bb(&["b2"]));
+}
+
+#[test]
+fn test_leaper_attacks() {
+    let square = |s: &str| alg(s).x + alg(s).y * 8;
+    let bb = |squares: &[&str]| squares.iter().fold(0u64, |acc, s| acc | alg(s).to_bitboard());
+
+    assert_eq!(attacks::knight(square("a8")), bb(&["b6", "c7"]));
+    assert_eq!(attacks::knight(square("d4")).count_ones(), 8);
+    assert_eq!(attacks::knight(square("h1")), bb(&["g3", "f2"]));
+    assert_eq!(attacks::king(square("e1")), bb(&["d1", "f1", "d2", "e2", "f2"]));
+    assert_eq!(attacks::king(square("a8")), bb(&["b8", "a7", "b7"]));
+
+    assert_eq!(attacks::pawn(square("e2"), PieceColor::White), bb(&["d3", "f3"]));
+    assert_eq!(attacks::pawn(square("a7"), PieceColor::Black), bb(&["b6"]));
+    assert_eq!(attacks::pawn(square("h4"), PieceColor::White), bb(&["g5"]));
+}
@@ -82,4 +82,16 @@ fn test_king_checked_en_passant() {
     println!("{:?}", board.get_total_legal_moves(None));
 
     assert_eq!(board.get_total_legal_moves(None).len(), 7);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_double_check() {
+    let mut board = Board::from_fen("4k3/n7/8/1B6/8/8/8/4R1K1 b - - 0 1");
+
+    assert_ne!(board.get_check(PieceColor::Black).double_checked, 0u64);
+
+    let mut moves: Vec<String> = board.get_total_legal_moves(None).iter().map(|m| m.to_uci()).collect();
+    moves.sort();
+
+    assert_eq!(moves, vec!["e8d8", "e8f7", "e8f8"]);
+}
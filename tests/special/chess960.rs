@@ -0,0 +1,109 @@
+use mchess::board::Board;
+use mchess::moves::Move;
+use mchess::perft::perft;
+use mchess::piece::PieceColor;
+use mchess::protocol::UciProtocol;
+
+#[test]
+fn test_chess960_perft() {
+    let positions = [
+        ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12189]),
+        ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18002]),
+        ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", [20, 479, 10471]),
+        ("1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9", [29, 502, 14569]),
+        ("rbbqn1kr/pp2p1pp/6n1/2pp1p2/2P4P/P7/BP1PPPP1/R1BQNNKR w HAha - 0 9", [27, 916, 25798]),
+        ("rqbbknr1/1ppp2pp/p5n1/4pp2/P7/1PP5/1Q1PPPPP/R1BBKNRN w GAga - 0 9", [24, 600, 15347])
+    ];
+
+    for (fen, expected) in positions {
+        let mut board = Board::from_fen(fen);
+        assert!(board.chess960, "Expected {} to be detected as Chess960", fen);
+
+        for (depth, &nodes) in expected.iter().enumerate() {
+            assert_eq!(perft(&mut board, depth as u32 + 1), nodes, "Perft failed at depth {} for {}", depth + 1, fen);
+        }
+    }
+}
+
+#[test]
+fn test_chess960_castling_rights() {
+    let board = Board::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+    assert_eq!(board.castling.white_rooks, (7, 5));
+    assert_eq!(board.castling.black_rooks, (7, 5));
+
+    let board = Board::from_fen("1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w KQkq - 0 9");
+    assert_eq!(board.castling.white_rooks, (5, 1));
+    assert_eq!(board.castling.black_rooks, (5, 1));
+    assert_eq!(board.to_fen(), "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w KQkq - 0 9");
+
+    let board = Board::startpos();
+    assert!(!board.chess960);
+    assert_eq!(board.castling.white_rooks, (7, 0));
+}
+
+#[test]
+fn test_chess960_xfen() {
+    // both white rooks are on the queenside of the king, so the inner one needs its file
+    let fen = "4k3/8/8/8/8/8/8/RR2K3 w B - 0 1";
+    let board = Board::from_fen(fen);
+    assert_eq!(board.castling.white_rooks.1, 1);
+    assert!(!board.castling.can_castle_ks(PieceColor::White));
+    assert_eq!(board.to_fen(), fen);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/RR2K3 w Q - 0 1");
+    assert_eq!(board.castling.white_rooks.1, 0);
+
+    assert!(Board::try_from_fen("4k3/8/8/8/8/8/8/RR2K3 w C - 0 1").is_err());
+}
+
+#[test]
+fn test_chess960_castling_moves() {
+    // king on b1 and rook on a1, castling queenside lands the king on c1 next to the rook on d1
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/RK6 w Q - 0 1");
+    let castle = Move::from_uci("b1a1", &mut board).unwrap();
+    assert_eq!(castle.to_san(&board), "O-O-O");
+    assert_eq!(castle.to_uci_chess960(), "b1a1");
+    assert!(Move::from_uci("b1c1", &mut board).is_ok_and(|m| m.with.is_none()));
+
+    let fen = board.to_fen();
+    let history = board.make_move(&castle);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+    assert_eq!(board.hash, Board::from_fen(&board.to_fen()).hash);
+    board.unmake_move(&castle, &history);
+    assert_eq!(board.to_fen(), fen);
+
+    // the king stays on g1 and only the rook moves
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/6KR w H - 0 1");
+    let castle = Move::from_uci("g1h1", &mut board).unwrap();
+    assert_eq!(castle.to_san(&board), "O-O");
+    let history = board.make_move(&castle);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+    assert_eq!(board.hash, Board::from_fen(&board.to_fen()).hash);
+    board.unmake_move(&castle, &history);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/6KR w K - 0 1");
+
+    // moving the castling rook away would expose the king on c1 to the rook on a1
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/rR1K4 w B - 0 1");
+    assert!(Move::from_uci("d1b1", &mut board).is_err());
+}
+
+#[test]
+fn test_chess960_option() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
+
+    protocol.set_option("setoption name UCI_Chess960 value true", &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap().trim(), "info string Setting UCI_Chess960 to true");
+
+    let mut output = Vec::new();
+    protocol.handle_position("position fen rk5r/8/8/8/8/8/8/RK5R w HAha - 0 1 moves b1h1 b8a8", &mut output).unwrap();
+    assert!(output.is_empty(), "Unexpected output: {}", String::from_utf8(output).unwrap());
+
+    // turning the option off applies to the position already set up
+    protocol.handle_position("position startpos moves e2e4", &mut Vec::new()).unwrap();
+    assert!(protocol.board().chess960);
+
+    protocol.set_option("setoption name UCI_Chess960 value false", &mut Vec::new()).unwrap();
+    assert!(!protocol.board().chess960);
+    assert!(!protocol.game().start().chess960);
+}
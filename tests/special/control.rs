@@ -124,4 +124,47 @@ fn test_multiple_moves_control_consistency() {
     }
     
     assert!(control_bitboards_match(&board.control_bitboards, &initial_control), "Control bitboards changed after multiple moves/unmakes");
-}
\ No newline at end of file
+}
+
+fn control_by_square(board: &Board) -> Vec<(u64, Vec<String>)> {
+    let mut squares: Vec<(u64, Vec<String>)> = board.control_bitboards.control_entries.iter()
+        .map(|(&square, entries)| {
+            let mut entries: Vec<String> = entries.iter()
+                .map(|e| format!("{:?} {:?} {:?} {:?} {}", e.origin.pos, e.origin.piece_type, e.control_type, e.threat, e.obscured))
+                .collect();
+            entries.sort();
+            (square, entries)
+        })
+        .collect();
+    squares.sort();
+    squares
+}
+
+#[test]
+fn test_incremental_control_matches_recalculation() {
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let initial_control = board.control_bitboards.clone();
+
+    let mut histories = Vec::new();
+
+    for i in 0..24 {
+        let moves = board.get_total_legal_moves(None);
+        if moves.is_empty() { break; }
+
+        let m = moves[(i * 7) % moves.len()].clone();
+        let history = board.make_move(&m);
+
+        let fresh = Board::from_fen(&board.to_fen());
+        assert_eq!(control_by_square(&board), control_by_square(&fresh), "Control entries differ after {:?}", m);
+        assert_eq!(board.get_check(board.turn).checked, fresh.get_check(fresh.turn).checked);
+        assert_eq!(board.get_check(board.turn).double_checked, fresh.get_check(fresh.turn).double_checked);
+
+        histories.push((m, history));
+    }
+
+    for (m, history) in histories.into_iter().rev() {
+        board.unmake_move(&m, &history);
+    }
+
+    assert!(control_bitboards_match(&board.control_bitboards, &initial_control), "Control bitboards changed after incremental moves/unmakes");
+}
@@ -0,0 +1,144 @@
+use mchess::board::{Board, PositionError};
+use mchess::piece::{PieceColor, PieceType};
+
+use crate::common::alg;
+
+fn assert_coherent(board: &mut Board) {
+    let fen = board.to_fen();
+    let mut fresh = Board::from_fen(&fen);
+
+    assert_eq!(board.hash, board.polyglot_key(), "{fen}");
+    assert_eq!(board.hash, fresh.hash, "{fen}");
+    assert_eq!(board.bb.all_pieces, fresh.bb.all_pieces, "{fen}");
+    assert_eq!(board.piece_count(), fresh.piece_count(), "{fen}");
+
+    let control = &board.control_bitboards;
+    let fresh_control = &fresh.control_bitboards;
+    assert_eq!(control.white_control, fresh_control.white_control, "{fen}");
+    assert_eq!(control.black_control, fresh_control.black_control, "{fen}");
+    assert_eq!(control.white_attack, fresh_control.white_attack, "{fen}");
+    assert_eq!(control.black_attack, fresh_control.black_attack, "{fen}");
+
+    assert_eq!(board.white_check.checked, fresh.white_check.checked, "{fen}");
+    assert_eq!(board.black_check.checked, fresh.black_check.checked, "{fen}");
+
+    let mut moves: Vec<String> = board.get_total_legal_moves(None).iter().map(|m| m.to_uci()).collect();
+    let mut fresh_moves: Vec<String> = fresh.get_total_legal_moves(None).iter().map(|m| m.to_uci()).collect();
+    moves.sort();
+    fresh_moves.sort();
+    assert_eq!(moves, fresh_moves, "{fen}");
+}
+
+#[test]
+fn test_build_startpos() {
+    let mut board = Board::startpos();
+    board.clear();
+
+    assert_eq!(board.piece_count(), 0);
+    assert_eq!(board.to_fen(), "8/8/8/8/8/8/8/8 w - - 0 1");
+
+    let back_rank = [PieceType::Rook, PieceType::Knight, PieceType::Bishop, PieceType::Queen, PieceType::King, PieceType::Bishop, PieceType::Knight, PieceType::Rook];
+    for (x, piece_type) in back_rank.into_iter().enumerate() {
+        board.put_piece(piece_type, PieceColor::Black, alg(&format!("{}8", (b'a' + x as u8) as char)));
+        board.put_piece(PieceType::Pawn, PieceColor::Black, alg(&format!("{}7", (b'a' + x as u8) as char)));
+        board.put_piece(PieceType::Pawn, PieceColor::White, alg(&format!("{}2", (b'a' + x as u8) as char)));
+        board.put_piece(piece_type, PieceColor::White, alg(&format!("{}1", (b'a' + x as u8) as char)));
+    }
+
+    for color in [PieceColor::White, PieceColor::Black] {
+        board.set_castling(color, true, Some(7)).unwrap();
+        board.set_castling(color, false, Some(0)).unwrap();
+    }
+
+    assert_eq!(board.to_fen(), Board::startpos().to_fen());
+    assert_eq!(board.hash, Board::startpos().hash);
+    assert!(!board.chess960);
+    assert_coherent(&mut board);
+
+    let moves = board.get_total_legal_moves(None);
+    assert_eq!(moves.len(), 20);
+}
+
+#[test]
+fn test_put_and_remove() {
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+
+    let rook = board.remove_piece(alg("h1")).unwrap();
+    assert_eq!(rook.piece_type, PieceType::Rook);
+    assert_eq!(board.to_fen(), "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K3 w Qkq - 0 1");
+    assert_coherent(&mut board);
+
+    assert!(board.remove_piece(alg("d4")).is_none());
+
+    // replacing a piece takes the old one off first
+    board.put_piece(PieceType::Knight, PieceColor::White, alg("e8"));
+    board.put_piece(PieceType::King, PieceColor::Black, alg("g8"));
+    assert_eq!(board.to_fen(), "r3N1kr/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K3 w Q - 0 1");
+    assert_coherent(&mut board);
+
+    // the black king is in check now
+    board.put_piece(PieceType::Knight, PieceColor::White, alg("h6"));
+    board.set_turn(PieceColor::Black);
+    assert_ne!(board.black_check.checked, 0);
+    assert_coherent(&mut board);
+
+    for m in board.get_total_legal_moves(None) {
+        let history = board.make_move(&m);
+        assert_eq!(board.hash, board.polyglot_key(), "{}", m.to_uci());
+        board.unmake_move(&m, &history);
+    }
+    assert_coherent(&mut board);
+}
+
+#[test]
+fn test_en_passant_editing() {
+    let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1");
+
+    assert_eq!(board.set_en_passant(Some(alg("d6"))), Ok(()));
+    assert_eq!(board.to_fen(), "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    assert!(board.get_total_legal_moves(None).iter().any(|m| m.to_uci() == "e5d6"));
+    assert_coherent(&mut board);
+
+    assert_eq!(board.set_en_passant(Some(alg("e6"))), Err(PositionError::InvalidEnPassant(alg("e6"))));
+    assert_eq!(board.target_square, Some(alg("d6")));
+
+    // blocking the start square of the pushed pawn
+    board.put_piece(PieceType::Knight, PieceColor::Black, alg("d7"));
+    assert_eq!(board.target_square, None);
+    assert_coherent(&mut board);
+
+    board.remove_piece(alg("d7"));
+    board.set_en_passant(Some(alg("d6"))).unwrap();
+    board.remove_piece(alg("d5"));
+    assert_eq!(board.target_square, None);
+    assert_coherent(&mut board);
+
+    board.put_piece(PieceType::Pawn, PieceColor::Black, alg("d5"));
+    board.set_en_passant(Some(alg("d6"))).unwrap();
+    board.set_turn(PieceColor::Black);
+    assert_eq!(board.target_square, None);
+    assert_coherent(&mut board);
+}
+
+#[test]
+fn test_castling_editing() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w - - 0 1");
+
+    assert_eq!(board.set_castling(PieceColor::Black, true, Some(7)), Err(PositionError::InvalidCastlingRights(PieceColor::Black, true)));
+    assert_eq!(board.set_castling(PieceColor::White, true, Some(0)), Err(PositionError::InvalidCastlingRights(PieceColor::White, true)));
+
+    board.set_castling(PieceColor::White, true, Some(7)).unwrap();
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R3K2R w K - 0 1");
+    assert!(board.get_total_legal_moves(None).iter().any(|m| m.to_uci() == "e1g1"));
+    assert_coherent(&mut board);
+
+    board.set_castling(PieceColor::White, true, None).unwrap();
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R3K2R w - - 0 1");
+    assert!(!board.get_total_legal_moves(None).iter().any(|m| m.to_uci() == "e1g1"));
+    assert_coherent(&mut board);
+
+    board.set_castling(PieceColor::White, false, Some(0)).unwrap();
+    board.remove_piece(alg("e1"));
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R6R w - - 0 1");
+    assert_eq!(board.hash, board.polyglot_key());
+}
@@ -1,6 +1,6 @@
 use core::f64;
 
-use mchess::{board::{Board, ResultType}, evaluation::{evaluate, evaluate_kings_safety}, search::Minimax};
+use mchess::{board::{Board, DrawReason, ResultType}, evaluation::{evaluate, evaluate_kings_safety}, search::Minimax};
 
 #[test]
 fn test_evaluation() {
@@ -15,13 +15,13 @@ fn test_evaluation() {
 
 #[test]
 fn test_draw() {
-    let mut board = Board::from_fen("5k2/8/3b4/8/5B2/8/2K5/8 w - - 0 1");
+    let board = Board::from_fen("5k2/8/3b4/8/5B2/8/2K5/8 w - - 0 1");
 
-    assert_eq!(board.get_result(), ResultType::Draw);
+    assert_eq!(board.get_result(), ResultType::Draw(DrawReason::InsufficientMaterial));
 
-    let mut board = Board::from_fen("5k2/8/3b4/8/4B3/8/2K5/8 w - - 0 1");
+    let board = Board::from_fen("5k2/8/3b4/8/4B3/8/2K5/8 w - - 0 1");
 
-    assert_ne!(board.get_result(), ResultType::Draw);
+    assert_eq!(board.get_result(), ResultType::None);
 }
 
 #[test]
@@ -32,4 +32,14 @@ fn evaluate_king_safety() {
 
     let mut engine = Minimax::new();
     println!("{:?}", engine.search(&mut board, 7, f64::NEG_INFINITY, f64::INFINITY, true));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_quiescence_check_evasions() {
+    // black has to step out of check with Ka7, after which Qxb7 mates
+    let mut board = Board::from_fen("k6R/1p6/8/8/4B3/8/8/1Q4K1 b - - 0 1");
+    let mut engine = Minimax::new();
+
+    let value = engine.quiescence(&mut board, f64::NEG_INFINITY, f64::INFINITY, false, 8);
+    assert!(value >= 1e9, "mate after the only evasion wasn't found: {value}");
+}
@@ -0,0 +1,105 @@
+use mchess::{board::{Board, FenError}, piece::PieceColor, protocol::UciProtocol};
+
+const POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10"
+];
+
+fn assert_round_trip(board: &Board) {
+    let fen = board.to_fen();
+    let restored = Board::from_fen(&fen);
+
+    assert_eq!(restored.to_fen(), fen, "FEN changed after round trip");
+    assert_eq!(restored.hash, board.hash, "Hash mismatch for {}", fen);
+}
+
+#[test]
+fn test_to_fen() {
+    for fen in POSITIONS {
+        let board = Board::from_fen(fen);
+
+        assert_eq!(board.to_fen(), fen);
+    }
+}
+
+#[test]
+fn test_to_fen_en_passant() {
+    let fen = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+    let board = Board::from_fen(fen);
+
+    assert_eq!(board.to_fen(), fen);
+}
+
+#[test]
+fn test_fen_round_trip() {
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen);
+        assert_round_trip(&board);
+
+        for m in board.get_total_legal_moves(None) {
+            let history = board.make_move(&m);
+            assert_round_trip(&board);
+
+            for m2 in board.get_total_legal_moves(None) {
+                let history2 = board.make_move(&m2);
+                assert_round_trip(&board);
+                board.unmake_move(&m2, &history2);
+            }
+
+            board.unmake_move(&m, &history);
+        }
+    }
+}
+
+#[test]
+fn test_fen_errors() {
+    let cases = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", FenError::InvalidFieldCount(4)),
+        ("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidRankCount(7)),
+        ("rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidRankLength(1)),
+        ("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidPiece('9')),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1", FenError::InvalidPiece('X')),
+        ("rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1", FenError::MissingKing(PieceColor::Black)),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKKBNR w kq - 0 1", FenError::TooManyKings(PieceColor::White)),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", FenError::InvalidTurn("x".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1", FenError::InvalidCastlingRights("KQkq".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1", FenError::InvalidCastlingRights("KQkx".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1", FenError::InvalidEnPassant("e3".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 1", FenError::InvalidEnPassant("e6".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - a 1", FenError::InvalidHalfmoveClock("a".to_string())),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0", FenError::InvalidMoveNumber("0".to_string())),
+        ("4k3/8/8/8/8/8/8/4K2r b - - 0 1", FenError::OpponentInCheck),
+        ("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1", FenError::OpponentInCheck),
+    ];
+
+    for (fen, expected) in cases {
+        match Board::try_from_fen(fen) {
+            Ok(_) => panic!("Expected {:?} for {}", expected, fen),
+            Err(e) => assert_eq!(e, expected, "Wrong error for {}", fen)
+        }
+    }
+}
+
+#[test]
+fn test_position_invalid_fen() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
+
+    protocol.handle_position("position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq", &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("info string Invalid FEN"), "Unexpected output: {}", output);
+}
+
+#[test]
+fn test_from_fen_permissive() {
+    // from_fen only rejects malformed input, legality is checked by try_from_fen
+    let fen = "k7/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+
+    assert_eq!(Board::from_fen(fen).to_fen(), fen);
+    assert_eq!(Board::try_from_fen(fen).err(), Some(FenError::OpponentInCheck));
+}
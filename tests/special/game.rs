@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use mchess::board::{Board, DrawReason, ResultType};
+use mchess::game::Game;
+use mchess::pgn::{read_game, write_game};
+use mchess::piece::PieceColor;
+use mchess::protocol::UciProtocol;
+
+#[test]
+fn test_undo_redo() {
+    let mut game = Game::new();
+
+    for m in ["e2e4", "e7e5", "g1f3", "b8c6"] {
+        game.play_uci(m).unwrap();
+    }
+
+    let fen = game.board().to_fen();
+    assert_eq!(game.sans(), vec!["e4", "e5", "Nf3", "Nc6"]);
+    assert_eq!(game.last_move().unwrap().m.to_uci(), "b8c6");
+
+    assert!(game.undo());
+    assert!(game.undo());
+    assert_eq!(game.ply(), 2);
+    assert_eq!(game.len(), 4);
+    assert_eq!(game.board().to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+
+    assert!(game.redo());
+    assert!(game.redo());
+    assert!(!game.redo());
+    assert_eq!(game.board().to_fen(), fen);
+
+    assert!(game.jump_to(0));
+    assert_eq!(game.board().to_fen(), game.start().to_fen());
+    assert_eq!(game.board().hash, game.start().hash);
+    assert!(!game.undo());
+    assert!(!game.jump_to(5));
+
+    assert!(game.jump_to(4));
+    assert_eq!(game.board().to_fen(), fen);
+    assert_eq!(game.board().hash, game.board().polyglot_key());
+}
+
+#[test]
+fn test_new_move_drops_redo() {
+    let mut game = Game::new();
+
+    for m in ["e2e4", "e7e5", "g1f3"] {
+        game.play_uci(m).unwrap();
+    }
+
+    game.jump_to(1);
+    game.play_san("c5").unwrap();
+
+    assert_eq!(game.len(), 2);
+    assert_eq!(game.sans(), vec!["e4", "c5"]);
+    assert!(!game.redo());
+    assert!(game.play_uci("e2e4").is_err());
+    assert!(game.play_san("Ke3").is_err());
+    assert_eq!(game.len(), 2);
+}
+
+#[test]
+fn test_tags_and_clocks() {
+    let mut game = Game::from_board(Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1"));
+
+    game.set_tag("Event", "Casual");
+    game.set_tag("White", "mchess");
+    game.set_tag("Event", "Rated");
+    assert_eq!(game.tag("Event"), Some("Rated"));
+    assert_eq!(game.tags()[1], ("White".to_string(), "mchess".to_string()));
+    assert_eq!(game.tag("Black"), None);
+
+    game.play_uci("e1g1").unwrap();
+    game.set_clock(Duration::from_secs(60));
+    game.play_uci("e8d7").unwrap();
+    game.set_clock(Duration::from_secs(55));
+
+    assert_eq!(game.moves()[0].san, "O-O");
+    assert_eq!(game.clock(PieceColor::White), Some(Duration::from_secs(60)));
+    assert_eq!(game.clock(PieceColor::Black), Some(Duration::from_secs(55)));
+
+    // a move without a clock keeps the last time the side had
+    game.play_uci("g1h1").unwrap();
+    assert_eq!(game.clock(PieceColor::White), Some(Duration::from_secs(60)));
+
+    game.jump_to(1);
+    assert_eq!(game.clock(PieceColor::Black), None);
+}
+
+#[test]
+fn test_result() {
+    let mut game = Game::new();
+
+    for _ in 0..2 {
+        for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            game.play_uci(m).unwrap();
+        }
+    }
+
+    assert_eq!(game.result(), ResultType::Claimable(DrawReason::ThreefoldRepetition));
+    assert!(game.claim_draw());
+    assert_eq!(game.result(), ResultType::Draw(DrawReason::ThreefoldRepetition));
+
+    game.undo();
+    assert_eq!(game.result(), ResultType::None);
+    assert!(!game.claim_draw());
+
+    game.redo();
+    assert_eq!(game.result(), ResultType::Draw(DrawReason::ThreefoldRepetition));
+
+    let mut game = Game::new();
+    for m in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+        game.play_uci(m).unwrap();
+    }
+    assert_eq!(game.result(), ResultType::BlackCheckmate);
+    assert_eq!(game.last_move().unwrap().san, "Qh4#");
+}
+
+#[test]
+fn test_protocol_game() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
+
+    protocol.handle_position("position startpos moves e2e4 e7e5 g1f3 e2e4", &mut output).unwrap();
+
+    assert_eq!(protocol.game().sans(), vec!["e4", "e5", "Nf3"]);
+    assert_eq!(protocol.board().turn, PieceColor::Black);
+}
+
+#[test]
+fn test_null_move() {
+    let mut game = Game::new();
+
+    game.play_uci("e2e4").unwrap();
+    let fen = game.board().to_fen();
+    let hash = game.board().hash;
+
+    game.play_uci("0000").unwrap();
+    game.play_san("d4").unwrap();
+    assert_eq!(game.sans(), vec!["e4", "--", "d4"]);
+    assert_eq!(game.played()[1].m.to_uci(), "0000");
+
+    assert!(game.jump_to(1));
+    assert_eq!(game.board().to_fen(), fen);
+    assert_eq!(game.board().hash, hash);
+
+    assert!(game.redo());
+    assert_eq!(game.board().turn, PieceColor::White);
+    assert!(game.redo());
+    assert_eq!(game.board().hash, game.board().polyglot_key());
+
+    // the pgn writes it as -- and reads it back
+    let pgn = write_game(&game);
+    let read = read_game(&pgn).unwrap();
+    assert_eq!(read.board().to_fen(), game.board().to_fen());
+}
@@ -0,0 +1,99 @@
+use mchess::board::{Board, DrawReason, ResultType};
+use mchess::evaluation::EvaluationResult;
+use mchess::moves::{Move, MoveNotation, PackedMove, Position};
+use mchess::piece::{PieceColor, PieceType};
+use mchess::search::SearchResult;
+
+use crate::common::alg;
+
+#[test]
+fn test_board_as_fen() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        // Chess960 rights come out in X-FEN form
+        "nrbkqbrn/pppppppp/8/8/8/8/PPPPPPPP/NRBKQBRN w KQkq - 0 1"
+    ];
+
+    for fen in fens {
+        let board = Board::from_fen(fen);
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, format!("\"{fen}\""));
+
+        let back: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_fen(), fen);
+        assert_eq!(back.hash, board.hash);
+        assert_eq!(back.chess960, board.chess960);
+    }
+
+    assert!(serde_json::from_str::<Board>("\"8/8/8 w - - 0 1\"").is_err());
+}
+
+#[test]
+fn test_move_notation() {
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+
+    let m = Move::from_uci("e1c1", &mut board).unwrap();
+    let json = serde_json::to_string(&m.notation(&board)).unwrap();
+    assert_eq!(json, r#"{"uci":"e1c1","san":"O-O-O"}"#);
+
+    let notation: MoveNotation = serde_json::from_str(&json).unwrap();
+    assert_eq!(notation.to_move(&mut board).unwrap(), m);
+
+    for m in board.get_total_legal_moves(None) {
+        let notation: MoveNotation = serde_json::from_str(&serde_json::to_string(&m.notation(&board)).unwrap()).unwrap();
+        assert_eq!(notation.san, m.to_san(&board));
+        assert_eq!(notation.to_move(&mut board).unwrap(), m);
+    }
+}
+
+#[test]
+fn test_pieces_and_squares() {
+    assert_eq!(serde_json::to_string(&alg("e4")).unwrap(), "\"e4\"");
+    assert_eq!(serde_json::from_str::<Position>("\"a8\"").unwrap(), Position { x: 0, y: 0 });
+    assert!(serde_json::from_str::<Position>("\"i9\"").is_err());
+    assert!(serde_json::from_str::<Position>("\"e44\"").is_err());
+
+    let board = Board::startpos();
+    let king = board.get_piece_at(alg("e1").y, alg("e1").x).unwrap();
+    let json = serde_json::to_string(&king).unwrap();
+    assert_eq!(json, format!(r#"{{"piece_type":"King","color":"White","pos":"e1","index":{}}}"#, king.index));
+
+    let piece: mchess::piece::Piece = serde_json::from_str(&json).unwrap();
+    assert_eq!(piece.piece_type, PieceType::King);
+    assert_eq!(piece.color, PieceColor::White);
+    assert_eq!(piece.pos, alg("e1"));
+}
+
+#[test]
+fn test_search_results() {
+    let mut board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    let promotion = Move::from_uci("b7b8n", &mut board).unwrap().pack();
+    let push = Move::from_uci("e1e2", &mut board).unwrap().pack();
+
+    let result = SearchResult { value: 1.5, moves: vec![promotion, push, PackedMove::NULL] };
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(json, r#"{"value":1.5,"moves":["b7b8n","e1e2","0000"]}"#);
+
+    // the flags come back by replaying the moves on the board they were searched on
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let replayed = Move::from_uci(value["moves"][0].as_str().unwrap(), &mut board).unwrap().pack();
+    assert_eq!(replayed, promotion);
+    assert_eq!(replayed.promote_to(), Some(PieceType::Knight));
+
+    let evaluation = EvaluationResult { white: 3.0, black: 1.25 };
+    let json = serde_json::to_string(&evaluation).unwrap();
+    assert_eq!(json, r#"{"white":3.0,"black":1.25}"#);
+    assert_eq!(serde_json::from_str::<EvaluationResult>(&json).unwrap().to_value(), 1.75);
+}
+
+#[test]
+fn test_results() {
+    let board = Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+    assert_eq!(serde_json::to_string(&board.get_result()).unwrap(), r#""BlackCheckmate""#);
+
+    let result = ResultType::Claimable(DrawReason::ThreefoldRepetition);
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(json, r#"{"Claimable":"ThreefoldRepetition"}"#);
+    assert_eq!(serde_json::from_str::<ResultType>(&json).unwrap(), result);
+}
@@ -0,0 +1,97 @@
+use mchess::board::Board;
+use mchess::moves::Position;
+use mchess::piece::{PieceColor, PieceType};
+
+use crate::common::alg;
+
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "nrbkqbrn/pppppppp/8/8/8/8/PPPPPPPP/NRBKQBRN w GBgb - 0 1"
+];
+
+fn assert_mailbox_consistent(board: &Board) {
+    let mut count = 0;
+
+    for (index, square) in board.piece_list.iter().enumerate() {
+        if let Some(square) = *square {
+            let piece = board.mailbox[square].expect("Piece list points at an empty square");
+            assert_eq!(piece.index, index);
+            assert_eq!(piece.pos.x + piece.pos.y * 8, square);
+            assert_ne!(board.bb.all_pieces & piece.pos.to_bitboard(), 0, "{:?}", piece);
+            count += 1;
+        }
+    }
+
+    assert_eq!(count, board.bb.all_pieces.count_ones() as usize);
+    assert_eq!(board.mailbox.iter().flatten().count(), count);
+}
+
+#[test]
+fn test_mailbox_lookup() {
+    let board = Board::startpos();
+
+    assert_eq!(board.piece_count(), 32);
+    assert_mailbox_consistent(&board);
+
+    let king = board.get_piece_at(alg("e1").y, alg("e1").x).unwrap();
+    assert_eq!(king.piece_type, PieceType::King);
+    assert_eq!(king.color, PieceColor::White);
+    assert_eq!(board.get_piece(king.index).unwrap().pos, alg("e1"));
+
+    assert!(board.get_piece_at(alg("e4").y, alg("e4").x).is_none());
+    assert!(board.get_piece(32).is_none());
+    assert!(board.get_piece(1000).is_none());
+
+    // pieces come back in index order, which is FEN order
+    let indices: Vec<usize> = board.pieces().map(|piece| piece.index).collect();
+    assert_eq!(indices, (0..32).collect::<Vec<usize>>());
+    assert_eq!(board.pieces().next().unwrap().pos, Position { x: 0, y: 0 });
+}
+
+#[test]
+fn test_mailbox_make_unmake() {
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen);
+        let original = board.clone();
+
+        let moves = board.get_total_legal_moves(None);
+        for m in &moves {
+            let history = board.make_move(m);
+            assert_mailbox_consistent(&board);
+
+            let piece = board.get_piece(m.piece_index).unwrap();
+            assert_eq!(piece.pos, m.to, "{fen} {}", m.to_uci());
+            assert_eq!(piece.piece_type, m.promote_to.unwrap_or(m.piece_type));
+
+            if let Some(captured) = &m.captured {
+                assert!(board.get_piece(captured.index).is_none());
+            }
+
+            board.unmake_move(m, &history);
+            assert_mailbox_consistent(&board);
+            assert_eq!(board.piece_list, original.piece_list, "{fen} {}", m.to_uci());
+        }
+    }
+}
+
+#[test]
+fn test_deterministic_move_order() {
+    for fen in POSITIONS {
+        let mut first = Board::from_fen(fen);
+        let mut second = Board::from_fen(fen);
+
+        let a: Vec<u32> = first.generate_moves(None, false).iter().map(|m| m.raw()).collect();
+        let b: Vec<u32> = second.generate_moves(None, false).iter().map(|m| m.raw()).collect();
+
+        assert_eq!(a, b, "{fen}");
+
+        let m = first.get_total_legal_moves(None)[0].clone();
+        let history = first.make_move(&m);
+        first.unmake_move(&m, &history);
+
+        let c: Vec<u32> = first.generate_moves(None, false).iter().map(|m| m.raw()).collect();
+        assert_eq!(a, c, "{fen}");
+    }
+}
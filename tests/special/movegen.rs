@@ -0,0 +1,150 @@
+use mchess::board::Board;
+use mchess::movegen::{self, MovePicker};
+use mchess::moves::{MoveList, PackedMove, Position};
+use mchess::piece::PieceType;
+
+const POSITIONS: [&str; 7] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    // en passant that would expose the king along the rank
+    "8/8/8/KPp4r/8/8/8/6k1 w - c6 0 2",
+    "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+];
+
+// from, to and flag, the check marker is left out
+fn key(m: &PackedMove) -> u32 {
+    m.raw() & 0xffff
+}
+
+fn sorted(moves: impl Iterator<Item = PackedMove>) -> Vec<u32> {
+    let mut keys: Vec<u32> = moves.map(|m| key(&m)).collect();
+    keys.sort();
+    keys
+}
+
+fn compare(board: &mut Board, depth: u32) {
+    let legal = board.generate_legal_moves(board.turn, false);
+
+    let mut pseudo = MoveList::new();
+    movegen::generate_pseudo_legal(board, board.turn, &mut pseudo);
+
+    assert_eq!(
+        sorted(pseudo.iter().copied().filter(|&m| board.is_legal(m))),
+        sorted(legal.iter().map(|m| m.pack())),
+        "{}", board.to_fen()
+    );
+
+    if depth == 0 {
+        return;
+    }
+
+    for m in legal {
+        let history = board.make_move(&m);
+        compare(board, depth - 1);
+        board.unmake_move(&m, &history);
+    }
+}
+
+#[test]
+fn test_pseudo_legal_matches_legal() {
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen);
+        compare(&mut board, 2);
+    }
+}
+
+#[test]
+fn test_stages() {
+    let board = Board::from_fen(POSITIONS[4]);
+
+    let mut captures = MoveList::new();
+    movegen::generate_captures(&board, board.turn, &mut captures);
+    assert!(captures.iter().all(|m| m.is_capture() || m.is_promotion()));
+
+    let mut quiets = MoveList::new();
+    movegen::generate_quiets(&board, board.turn, &mut quiets);
+    assert!(quiets.iter().all(|m| !m.is_capture() && !m.is_promotion()));
+
+    assert!(captures.iter().any(|m| m.to_uci() == "d7c8q"));
+    assert!(quiets.iter().any(|m| m.to_uci() == "e1g1"));
+}
+
+#[test]
+fn test_move_picker() {
+    let mut board = Board::from_fen(POSITIONS[1]);
+    let legal = board.generate_legal_moves(board.turn, false);
+
+    let mut picker = MovePicker::new(&board, false);
+    let mut picked = Vec::new();
+    while let Some(m) = picker.next(&mut board, |_, _| {}) {
+        picked.push(m);
+    }
+
+    assert_eq!(sorted(picked.iter().copied()), sorted(legal.iter().map(|m| m.pack())));
+
+    // winning and even captures come first, losing ones after every quiet move
+    let first_quiet = picked.iter().position(|m| !m.is_capture()).unwrap();
+    let last_quiet = picked.iter().rposition(|m| !m.is_capture()).unwrap();
+    assert!(picked[..first_quiet].iter().all(|m| board.see(&board.unpack(*m)) >= 0));
+    assert!(picked[first_quiet..=last_quiet].iter().all(|m| !m.is_capture()));
+    assert!(picked[last_quiet + 1..].iter().all(|m| board.see(&board.unpack(*m)) < 0));
+    assert!(last_quiet + 1 < picked.len());
+
+    let mut picker = MovePicker::new(&board, true);
+    let mut captures = 0;
+    while let Some(m) = picker.next(&mut board, |moves, _| moves.reverse()) {
+        assert!(m.is_capture());
+        captures += 1;
+    }
+    assert_eq!(captures, 8);
+}
+
+#[test]
+fn test_move_picker_hints() {
+    let mut board = Board::from_fen(POSITIONS[1]);
+    let legal = board.generate_legal_moves(board.turn, false);
+    let find = |uci: &str| legal.iter().find(|m| m.to_uci() == uci).unwrap().pack();
+
+    let hash_move = find("e1g1");
+    let killers = [Some(find("a2a3")), Some(find("d5d6"))];
+
+    let mut picker = MovePicker::with_hints(&board, Some(hash_move), killers);
+    let mut picked = Vec::new();
+    while let Some(m) = picker.next(&mut board, |_, _| {}) {
+        picked.push(m);
+    }
+
+    assert_eq!(sorted(picked.iter().copied()), sorted(legal.iter().map(|m| m.pack())));
+    assert_eq!(picked[0], hash_move);
+
+    // the killers follow the good captures, ahead of the other quiet moves
+    let first_quiet = picked[1..].iter().position(|m| !m.is_capture()).unwrap() + 1;
+    assert_eq!(picked[first_quiet..first_quiet + 2], [killers[0].unwrap(), killers[1].unwrap()]);
+
+    // a hash move this position can't play is never handed out
+    let foreign = PackedMove::new(Position { x: 0, y: 0 }, Position { x: 0, y: 7 }, 0, PieceType::Rook, None);
+    let mut picker = MovePicker::with_hints(&board, Some(foreign), [None; 2]);
+    let mut count = 0;
+    while let Some(m) = picker.next(&mut board, |_, _| {}) {
+        assert_ne!(m, foreign);
+        count += 1;
+    }
+    assert_eq!(count, legal.len());
+}
+
+#[test]
+fn test_is_legal() {
+    // the pinned knight may not move, the king may not step onto an attacked square
+    let board = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1");
+
+    let mut moves = MoveList::new();
+    movegen::generate_pseudo_legal(&board, board.turn, &mut moves);
+
+    for m in &moves {
+        let legal = matches!(m.to_uci().as_str(), "e1d1" | "e1f1" | "e1d2" | "e1f2");
+        assert_eq!(board.is_legal(*m), legal, "{}", m.to_uci());
+    }
+}
@@ -0,0 +1,77 @@
+use mchess::board::Board;
+use mchess::moves::Move;
+use mchess::piece::PieceColor;
+
+fn legal_moves(board: &Board) -> Vec<String> {
+    let mut moves: Vec<String> = board.generate_legal_moves(board.turn, false).iter().map(|m| m.to_uci()).collect();
+    moves.sort();
+    moves
+}
+
+#[test]
+fn test_null_move() {
+    let mut board = Board::startpos();
+    let original = board.clone();
+
+    let history = board.make_null_move().unwrap();
+    assert_eq!(board.turn, PieceColor::Black);
+    assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 1 1");
+    assert_eq!(board.hash, board.polyglot_key());
+    assert_eq!(legal_moves(&board).len(), 20);
+
+    board.unmake_null_move(&history);
+    assert_eq!(board.to_fen(), original.to_fen());
+    assert_eq!(board.hash, original.hash);
+    assert_eq!(legal_moves(&board), legal_moves(&original));
+}
+
+#[test]
+fn test_null_move_clears_en_passant() {
+    let fens = [
+        "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+        // the rook looks through the pushed pawn at the king
+        "8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1"
+    ];
+
+    for fen in fens {
+        let mut board = Board::from_fen(fen);
+        let original = legal_moves(&board);
+
+        let history = board.make_null_move().unwrap();
+        assert_eq!(board.target_square, None);
+        assert_eq!(board.hash, board.polyglot_key());
+        assert_eq!(legal_moves(&board), legal_moves(&Board::from_fen(&board.to_fen())), "{}", fen);
+
+        board.unmake_null_move(&history);
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.hash, board.polyglot_key());
+        assert_eq!(legal_moves(&board), original, "{}", fen);
+    }
+}
+
+#[test]
+fn test_null_move_in_check() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/4r3/4K3 w - - 0 1");
+
+    assert!(board.make_null_move().is_none());
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/4r3/4K3 w - - 0 1");
+}
+
+#[test]
+fn test_null_move_repetitions() {
+    let mut board = Board::startpos();
+
+    for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        let m = Move::from_uci(m, &mut board).unwrap();
+        board.make_move(&m);
+    }
+    assert!(board.is_repetition(2));
+
+    let history = board.make_null_move().unwrap();
+    let history_black = board.make_null_move().unwrap();
+    assert!(!board.is_repetition(2));
+
+    board.unmake_null_move(&history_black);
+    board.unmake_null_move(&history);
+    assert!(board.is_repetition(2));
+}
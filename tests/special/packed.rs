@@ -0,0 +1,100 @@
+use mchess::board::Board;
+use mchess::moves::{MoveList, MoveType, PackedMove};
+use mchess::piece::PieceType;
+
+use crate::common::alg;
+
+const POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3"
+];
+
+#[test]
+fn test_pack_round_trip() {
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen);
+        let moves = board.get_total_legal_moves(None);
+        let packed = board.generate_moves(None, false);
+
+        assert_eq!(moves.len(), packed.len(), "{fen}");
+
+        for m in &moves {
+            let p = m.pack();
+            assert!(packed.contains(&p), "{fen} {:?}", m);
+
+            let unpacked = board.unpack(p);
+            assert_eq!(unpacked.from, m.from);
+            assert_eq!(unpacked.to, m.to);
+            assert_eq!(unpacked.move_type, m.move_type, "{fen} {:?}", m);
+            assert_eq!(unpacked.promote_to, m.promote_to);
+            assert_eq!(unpacked.piece_index, m.piece_index);
+            assert_eq!(unpacked.piece_type, m.piece_type);
+            assert_eq!(unpacked.captured.as_ref().map(|p| p.index), m.captured.as_ref().map(|p| p.index));
+            assert_eq!(unpacked.with.as_ref().map(|p| p.pos), m.with.as_ref().map(|p| p.pos));
+            assert_eq!(unpacked.pack(), p);
+        }
+    }
+}
+
+#[test]
+fn test_packed_flags() {
+    let mut board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    let moves = board.generate_moves(None, false);
+
+    let promotions: Vec<&PackedMove> = moves.iter().filter(|m| m.from() == alg("a7")).collect();
+    assert_eq!(promotions.len(), 8);
+    assert!(promotions.iter().all(|m| m.is_promotion() && m.piece_type() == PieceType::Pawn));
+
+    let capture = promotions.iter().find(|m| m.to() == alg("b8") && m.promote_to() == Some(PieceType::Queen)).unwrap();
+    assert!(capture.is_capture());
+    assert_eq!(capture.captured(), Some(PieceType::Knight));
+    assert_eq!(capture.to_uci(), "a7b8q");
+
+    let push = promotions.iter().find(|m| m.to() == alg("a8") && m.promote_to() == Some(PieceType::Knight)).unwrap();
+    assert!(!push.is_capture());
+    assert_eq!(push.captured(), None);
+
+    let mut board = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+    let moves = board.generate_moves(None, false);
+
+    let en_passant = moves.iter().find(|m| m.is_en_passant()).unwrap();
+    assert_eq!(en_passant.to_uci(), "e5f6");
+    assert!(en_passant.is_capture());
+    assert!(board.unpack(*en_passant).move_type.contains(&MoveType::EnPassant));
+
+    assert!(moves.iter().any(|m| m.flag() == PackedMove::DOUBLE_PUSH && m.to_uci() == "a2a4"));
+
+    let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let moves = board.generate_moves(None, false);
+
+    assert!(moves.iter().any(|m| m.flag() == PackedMove::KING_CASTLE && m.to_uci() == "e1g1"));
+    assert!(moves.iter().any(|m| m.flag() == PackedMove::QUEEN_CASTLE && m.to_uci() == "e1c1"));
+}
+
+#[test]
+fn test_move_list() {
+    let board = Board::startpos();
+    let mut list = MoveList::new();
+
+    assert!(list.is_empty());
+
+    board.generate_move_list(board.turn, false, &mut list);
+    assert_eq!(list.len(), 20);
+
+    list.retain(|m| m.piece_type() == PieceType::Knight);
+    assert_eq!(list.len(), 4);
+    assert!(list.iter().all(|m| m.piece_type() == PieceType::Knight));
+
+    list.sort_by_key(|m| m.raw());
+    let sorted: Vec<u32> = list.iter().map(|m| m.raw()).collect();
+    assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+
+    list.clear();
+    assert!(list.is_empty());
+    assert!(PackedMove::NULL.is_null());
+    assert_eq!(PackedMove::NULL.to_uci(), "0000");
+}
@@ -1,58 +1,9 @@
 use mchess::board::Board;
+use mchess::perft::{self, perft, EpdError, EpdPosition, Mismatch, PerftOptions, PerftTable};
+use mchess::protocol::UciProtocol;
 
-fn perft(board: &mut Board, depth: u32) -> u64 {
-    if depth == 0 { return 1; }
-    
-    let moves = board.get_total_legal_moves(None);
-    if depth == 1 { return moves.len() as u64; }
-    
-    let mut nodes = 0;
-    for m in moves {
-        let history = board.make_move(&m);
-        nodes += perft(board, depth - 1);
-        board.unmake_move(&m, &history);
-    }
-    nodes
-}
-
-fn split_perft(board: &mut Board, depth: u32) -> u64 {
-    if depth == 0 { return 1; }
-    
-    let moves = board.get_total_legal_moves(None);
-    if depth == 1 { 
-        for m in &moves {
-            println!("{:?}: 1", m);
-        }
-        return moves.len() as u64; 
-    }
-    
-    let mut total_nodes = 0;
-    
-    for m in moves {
-        let move_str = format!("{:?}", m);
-        
-        let history = board.make_move(&m);
-        let nodes = 
-        if format!("{:?}", m) == "todo" {
-            split_perft(board, depth - 1)
-        } else if format!("{:?}", m) == "remove" {
-            split_perft(board, depth - 1)
-        } else if format!("{:?}", m) == "these" {
-            split_perft(board, depth - 1)
-        } else if format!("{:?}", m) == "conditions" {
-            split_perft(board, depth - 1)
-        } else {
-            perft(board, depth - 1)
-        };
-        
-        println!("{}: {}", move_str, nodes);
-        
-        total_nodes += nodes;
-        board.unmake_move(&m, &history);
-    }
-    
-    println!("\nTotal: {}", total_nodes);
-    total_nodes
+fn split_perft(board: &Board, depth: u32, options: &PerftOptions) -> u64 {
+    perft::divide(board, depth, options).iter().map(|(_, nodes)| nodes).sum()
 }
 
 #[test]
@@ -117,36 +68,68 @@ fn test_perft() {
 
 #[test]
 fn test_split_perft() {
+    let options = PerftOptions::default();
+    let hashed = PerftOptions { threads: 3, hash_mb: 4 };
+
     // startpos
-    let mut board = Board::startpos();
+    let board = Board::startpos();
     let start = board.hash;
 
-    let result = split_perft(&mut board, 4);
-    let result2 = split_perft(&mut board, 4);
-
+    let divide = perft::divide(&board, 4, &options);
+    assert_eq!(divide.len(), 20);
+    assert_eq!(divide.iter().find(|(m, _)| m.to_uci() == "e2e4").unwrap().1, 13160);
+    assert_eq!(perft::divide(&board, 4, &hashed), divide, "Inconsistent results");
     assert_eq!(board.hash, start, "Board hash changed");
-    assert_eq!(result, result2, "Inconsistent results");
-    assert_eq!(result, 197281);
 
     // kiwipete
-    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    assert_eq!(split_perft(&board, 3, &options), 97862);
+    assert_eq!(split_perft(&board, 3, &hashed), 97862);
 
-    let result = split_perft(&mut board, 3);
-    let result2 = split_perft(&mut board, 3);
+    // position 3
+    let board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+    assert_eq!(split_perft(&board, 5, &hashed), 674624);
 
-    assert_eq!(result, result2, "Inconsistent results");
+    // position 5
+    let board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
+    assert_eq!(split_perft(&board, 4, &hashed), 2103487);
+}
 
-    assert_eq!(result, 97862);
+#[test]
+fn test_hashed_perft() {
+    let mut board = Board::from_fen("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10");
+    let mut table = PerftTable::new(8);
 
-    // position 3
-    let mut board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
-    let result = split_perft(&mut board, 5);
+    assert_eq!(perft::perft_hashed(&mut board, 3, &mut table), 89890);
+    assert_eq!(table.get(board.hash, 3), Some(89890));
+    assert_eq!(perft::perft_hashed(&mut board, 3, &mut table), 89890);
+}
 
-    assert_eq!(result, 674624);
+#[test]
+fn test_epd() {
+    let position = EpdPosition::parse("4k3/8/8/8/8/8/8/4K2R w K - ;D1 15 ;D2 66 ;D3 1197").unwrap();
+    assert_eq!(position.fen, "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert_eq!(position.counts, vec![(1, 15), (2, 66), (3, 1197)]);
+    assert!(position.check(None, &PerftOptions::default()).is_empty());
+
+    let position = EpdPosition::parse("4k3/8/8/8/8/8/8/4K2R w K - 0 1 ;D1 15 ;D2 67").unwrap();
+    assert_eq!(position.check(None, &PerftOptions::default()), vec![Mismatch { depth: 2, expected: 67, found: 66 }]);
+    assert!(position.check(Some(1), &PerftOptions::default()).is_empty());
+
+    assert_eq!(EpdPosition::parse("4k3/8/8/8/8/8/8/4K2R w K - ;D1 x").err(), Some(EpdError::InvalidCount("D1 x".to_string())));
+    assert!(matches!(EpdPosition::parse("4k3/8/8 w - - ;D1 1"), Err(EpdError::InvalidFen(_))));
+}
 
-    // position 5
-    let mut board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
-    let result = split_perft(&mut board, 4);
+#[test]
+fn test_uci_perft() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
 
-    assert_eq!(result, 2103487);
-}
\ No newline at end of file
+    protocol.handle_position("position startpos moves e2e4", &mut output).unwrap();
+    output.clear();
+    protocol.handle_go("go perft 2", &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("d7d5: 31\ne7e6: 30\ne7e5: 29\n"));
+    assert!(output.ends_with("\nNodes searched: 600\n"));
+}
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use mchess::board::{Board, ResultType};
+use mchess::game::Game;
+use mchess::moves::SanError;
+use mchess::pgn::{self, PgnError, PgnGame, PgnReader};
+use mchess::piece::PieceColor;
+
+const ANNOTATED: &str = r#"[Event "Casual \"blitz\""]
+[Site "?"]
+[White "Alice"]
+[Black "Bob"]
+[Result "1-0"]
+[ECO "C20"]
+
+{Opening notes} 1. e4 $1 e5 {Symmetrical \} reply} 2. Nf3!? (2. Bc4 Nf6 (2... Bc5) 3. d3) 2... Nc6
+; rest of line comment
+3. Bb5 a6?! 4. Ba4 Nf6 5. O-O 1-0
+
+% escaped line that is ignored
+[Event "Second"]
+
+1. d4 d5 *
+"#;
+
+#[test]
+fn test_read() {
+    let games: Vec<PgnGame> = PgnReader::new(ANNOTATED).collect::<Result<_, _>>().unwrap();
+    assert_eq!(games.len(), 2);
+
+    let game = &games[0];
+    assert_eq!(game.tag("Event"), Some("Casual \"blitz\""));
+    assert_eq!(game.tag("ECO"), Some("C20"));
+    assert_eq!(game.result, "1-0");
+    assert_eq!(game.moves.len(), 9);
+
+    assert_eq!(game.moves[0].pre_comment.as_deref(), Some("Opening notes"));
+    assert_eq!(game.moves[0].nags, vec![1]);
+    assert_eq!(game.moves[1].comment.as_deref(), Some("Symmetrical } reply"));
+    assert_eq!(game.moves[2].nags, vec![5]);
+    assert_eq!(game.moves[3].comment.as_deref(), Some("rest of line comment"));
+    assert_eq!(game.moves[5].nags, vec![6]);
+    assert_eq!(game.moves[8].san, "O-O");
+
+    let variation = &game.moves[2].variations[0];
+    assert_eq!(variation.iter().map(|m| m.san.as_str()).collect::<Vec<_>>(), vec!["Bc4", "Nf6", "d3"]);
+    assert_eq!(variation[1].variations[0][0].san, "Bc5");
+
+    assert_eq!(games[1].tag("Event"), Some("Second"));
+    assert_eq!(games[1].result, "*");
+    assert_eq!(games[1].moves.len(), 2);
+}
+
+#[test]
+fn test_comments_between_games() {
+    let text = "; exported by hand\n[Event \"a\"]\n1. e4 *\n; note\n\n{between} [Event \"b\"]\n1. d4 *\n{Tagless} 1. c4 *\n{trailing}\n; end";
+    let games: Vec<PgnGame> = PgnReader::new(text).collect::<Result<_, _>>().unwrap();
+
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].tag("Event"), Some("a"));
+    assert_eq!(games[1].tag("Event"), Some("b"));
+    assert_eq!(games[1].moves[0].san, "d4");
+    assert_eq!(games[1].moves[0].pre_comment, None);
+
+    // without tags the comment opens the movetext
+    assert_eq!(games[2].moves[0].san, "c4");
+    assert_eq!(games[2].moves[0].pre_comment.as_deref(), Some("Tagless"));
+
+    assert_eq!(PgnReader::new("; nothing here\n{at all}").count(), 0);
+}
+
+#[test]
+fn test_write_round_trip() {
+    let games: Vec<PgnGame> = PgnReader::new(ANNOTATED).collect::<Result<_, _>>().unwrap();
+    let text = games[0].to_string();
+
+    assert!(text.starts_with("[Event \"Casual \\\"blitz\\\"\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n"));
+    assert!(text.contains(concat!(
+        "[ECO \"C20\"]\n\n",
+        "{Opening notes} 1. e4 $1 e5 {Symmetrical \\} reply} 2. Nf3 $5 (2. Bc4 Nf6 (2...\n",
+        "Bc5) 3. d3) 2... Nc6 {rest of line comment} 3. Bb5 a6 $6 4. Ba4 Nf6 5. O-O 1-0\n"
+    )));
+    assert!(text.lines().all(|line| line.len() <= 80));
+    assert!(text.trim_end().ends_with("1-0"));
+
+    let back: Vec<PgnGame> = PgnReader::new(&text).collect::<Result<_, _>>().unwrap();
+    assert_eq!(back.len(), 1);
+    assert_eq!(back[0].moves, games[0].moves);
+    assert_eq!(back[0].tag("Date"), Some("????.??.??"));
+    assert_eq!(back[0].to_string(), text);
+}
+
+#[test]
+fn test_game_export() {
+    let mut game = Game::from_board(Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"));
+    game.set_tag("White", "mchess");
+
+    for san in ["e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+        game.play_san(san).unwrap();
+    }
+    game.jump_to(1);
+    game.set_clock(Duration::from_secs(3723));
+    game.jump_to(6);
+
+    let text = pgn::write_game(&game);
+    assert!(text.contains("[White \"mchess\"]"));
+    assert!(text.contains("[Result \"1-0\"]"));
+    assert!(text.contains("[SetUp \"1\"]\n[FEN \"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\"]"));
+    assert!(text.contains("1... e5 {[%clk 1:02:03]} 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0"));
+
+    let back = pgn::read_game(&text).unwrap();
+    assert_eq!(back.board().to_fen(), game.board().to_fen());
+    assert_eq!(back.result(), ResultType::WhiteCheckmate);
+    assert_eq!(back.clock(PieceColor::Black), Some(Duration::from_secs(3723)));
+    assert_eq!(back.sans(), game.sans());
+}
+
+#[test]
+fn test_errors() {
+    let mut games = PgnReader::new("1. e4 (1. d4 d5\n\n[Event \"Next\"]\n1. e4 *");
+    assert_eq!(games.next(), Some(Err(PgnError::UnterminatedVariation)));
+    assert_eq!(games.next().unwrap().unwrap().tag("Event"), Some("Next"));
+    assert_eq!(games.next(), None);
+
+    let mut games = PgnReader::new("1. e4 ) e5 *\n[Event \"Next\"]\n1. d4 *");
+    assert_eq!(games.next(), Some(Err(PgnError::UnexpectedToken(")".to_string()))));
+    assert_eq!(games.next().unwrap().unwrap().moves[0].san, "d4");
+
+    assert_eq!(PgnReader::new("1. e4 {never closed").next(), Some(Err(PgnError::UnterminatedComment)));
+    assert_eq!(PgnReader::new("[Event \"open\n1. e4 *").next(), Some(Err(PgnError::UnterminatedString)));
+    assert_eq!(PgnReader::new("1. e4 $ e5 *").next(), Some(Err(PgnError::InvalidNag("$".to_string()))));
+    assert_eq!(PgnReader::new("1. e4 (1. d4").next(), Some(Err(PgnError::UnterminatedVariation)));
+
+    assert_eq!(pgn::read_game("1. e4 e5 2. Ke3 *").err(), Some(PgnError::IllegalMove(3, SanError::IllegalMove("Ke3".to_string()))));
+}
@@ -44,4 +44,14 @@ fn test_phantom_pins() {
     assert_eq!(board.target_piece, white_pawn.index as i32);
     assert_eq!(board.get_legal_moves(black_pawn.index).len(), 1);
     assert!(board.is_phantom_pinned(pos2.y, pos2.x));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_phantom_pin_from_fen() {
+    let mut board = Board::from_fen("8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1");
+    let pos = alg("f4");
+
+    assert!(board.is_phantom_pinned(pos.y, pos.x));
+    assert!(board.is_pinned(pos.y, pos.x).is_none());
+    assert!(!board.get_total_legal_moves(None).iter().any(|m| m.to_uci() == "f4e3"));
+}
@@ -0,0 +1,84 @@
+use mchess::board::Board;
+use mchess::piece::PieceColor;
+use mchess::protocol::UciProtocol;
+use mchess::render::{self, SvgOptions};
+
+use crate::common::alg;
+
+#[test]
+fn test_text_diagrams() {
+    let board = Board::startpos();
+
+    assert_eq!(render::ascii(&board), concat!(
+        "8 r n b q k b n r\n",
+        "7 p p p p p p p p\n",
+        "6 . . . . . . . .\n",
+        "5 . . . . . . . .\n",
+        "4 . . . . . . . .\n",
+        "3 . . . . . . . .\n",
+        "2 P P P P P P P P\n",
+        "1 R N B Q K B N R\n",
+        "  a b c d e f g h\n"
+    ));
+    assert_eq!(format!("{:?}", board), render::ascii(&board));
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    let diagram = render::unicode(&board, PieceColor::Black);
+    let lines: Vec<&str> = diagram.lines().collect();
+
+    assert_eq!(lines[0], "1 · · · ♔ · · · ♖");
+    assert_eq!(lines[7], "8 · · · ♚ · · · ·");
+    assert_eq!(lines[8], "  h g f e d c b a");
+
+    let mask = alg("a8").to_bitboard() | alg("h1").to_bitboard();
+    let printed = render::bitboard(mask);
+    assert!(printed.starts_with("8 1 . . . . . . .\n"));
+    assert!(printed.contains("1 . . . . . . . 1\n"));
+}
+
+#[test]
+fn test_svg() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2r w - - 0 1");
+
+    let plain = render::svg(&board, &SvgOptions::default());
+    assert!(plain.starts_with("<svg"));
+    assert!(plain.trim_end().ends_with("</svg>"));
+    assert_eq!(plain.matches("<rect").count(), 64);
+    assert!(plain.contains("♔") && plain.contains("♚") && plain.contains("♜"));
+    // the white king is in check
+    assert_eq!(plain.matches("<circle").count(), 1);
+
+    let options = SvgOptions {
+        last_move: Some((alg("h8"), alg("h1"))),
+        highlights: vec![alg("e1")],
+        arrows: vec![(alg("e1"), alg("d2"))],
+        attacked_by: Some(PieceColor::Black),
+        ..SvgOptions::default()
+    };
+    let decorated = render::svg(&board, &options);
+    let black_control = board.control_bitboards.black_control.count_ones() as usize;
+
+    assert_eq!(decorated.matches("<rect").count(), 64 + black_control + 2 + 1);
+    assert_eq!(decorated.matches("<line").count(), 1);
+
+    let flipped = render::svg(&board, &SvgOptions { flipped: true, coordinates: false, ..SvgOptions::default() });
+    assert_ne!(flipped, plain);
+    assert!(!flipped.contains("font-family"));
+}
+
+#[test]
+fn test_display_command() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
+
+    protocol.handle_position("position startpos moves e2e4", &mut output).unwrap();
+    protocol.display(&mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("4 · · · · ♙ · · ·"));
+    assert!(output.contains("Fen: rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"));
+    assert!(output.contains("Key: 823C9B50FD114196"));
+
+    let svg = protocol.to_svg(false, None);
+    assert_eq!(svg.matches("#cdd26a").count(), 2);
+}
@@ -0,0 +1,75 @@
+use mchess::board::{Board, DrawReason, MoveInfo, ResultType};
+use mchess::moves::Move;
+
+fn play(board: &mut Board, uci: &str) -> (Move, MoveInfo) {
+    let m = board.get_total_legal_moves(None).into_iter()
+        .find(|m| format!("{:?}", m) == uci)
+        .expect("Move not found");
+    let history = board.make_move(&m);
+
+    (m, history)
+}
+
+#[test]
+fn test_threefold_repetition() {
+    let mut board = Board::startpos();
+
+    for _ in 0..2 {
+        assert!(!board.is_repetition(3));
+        for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            play(&mut board, m);
+        }
+    }
+
+    assert!(board.is_repetition(2));
+    assert!(board.is_repetition(3));
+    assert!(!board.is_repetition(4));
+    assert_eq!(board.get_result(), ResultType::Claimable(DrawReason::ThreefoldRepetition));
+}
+
+#[test]
+fn test_repetition_side_to_move() {
+    let mut board = Board::startpos();
+
+    // same placement is reached, but with black to move
+    for m in ["g1f3", "g8f6", "f3g1"] {
+        play(&mut board, m);
+    }
+
+    assert!(!board.is_repetition(2));
+}
+
+#[test]
+fn test_repetition_unmake() {
+    let mut board = Board::startpos();
+    let mut played = vec![];
+
+    for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        played.push(play(&mut board, m));
+    }
+
+    assert!(board.is_repetition(2));
+
+    let (m, history) = played.pop().unwrap();
+    board.unmake_move(&m, &history);
+
+    assert!(!board.is_repetition(2));
+    assert_eq!(board.hash_history.len(), 3);
+}
+
+#[test]
+fn test_repetition_reset_on_irreversible() {
+    let mut board = Board::startpos();
+    let mut played = vec![];
+
+    for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        played.push(play(&mut board, m));
+    }
+
+    let (m, history) = play(&mut board, "e2e4");
+    assert!(board.hash_history.is_empty());
+
+    board.unmake_move(&m, &history);
+    assert_eq!(board.hash_history.len(), 4);
+    assert!(board.is_repetition(2));
+}
@@ -0,0 +1,84 @@
+use mchess::board::{Board, DrawReason, ResultType};
+
+fn play(board: &mut Board, uci: &str) {
+    let m = board.get_total_legal_moves(None).into_iter()
+        .find(|m| format!("{:?}", m) == uci)
+        .expect("Move not found");
+    board.make_move(&m);
+}
+
+#[test]
+fn test_stalemate() {
+    let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+
+    assert_eq!(board.get_result(), ResultType::Stalemate);
+    assert_eq!(board.get_result().to_pgn(), "1/2-1/2");
+    assert!(board.get_result().is_end());
+}
+
+#[test]
+fn test_fifty_move_rule() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80");
+    assert_eq!(board.get_result(), ResultType::None);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80");
+    assert_eq!(board.get_result(), ResultType::Claimable(DrawReason::FiftyMoves));
+    assert!(!board.get_result().is_end());
+    assert_eq!(board.get_result().to_pgn(), "*");
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 80");
+    assert_eq!(board.get_result(), ResultType::Draw(DrawReason::SeventyFiveMoves));
+    assert!(board.get_result().is_end());
+}
+
+#[test]
+fn test_fifty_move_clock_reset() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80");
+
+    play(&mut board, "e2e4");
+
+    assert_eq!(board.halfmove_clock, 0);
+    assert_eq!(board.get_result(), ResultType::None);
+}
+
+#[test]
+fn test_fivefold_repetition() {
+    let mut board = Board::startpos();
+
+    for i in 0..4 {
+        for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            play(&mut board, m);
+        }
+
+        if i < 3 {
+            assert_ne!(board.get_result(), ResultType::Draw(DrawReason::FivefoldRepetition));
+        }
+    }
+
+    assert_eq!(board.get_result(), ResultType::Draw(DrawReason::FivefoldRepetition));
+    assert_eq!(board.get_result().to_pgn(), "1/2-1/2");
+}
+
+#[test]
+fn test_insufficient_material() {
+    let board = Board::from_fen("8/8/3k4/8/8/3K4/8/8 w - - 0 1");
+    assert_eq!(board.get_result(), ResultType::Draw(DrawReason::InsufficientMaterial));
+
+    let board = Board::from_fen("8/8/3k4/8/8/3K1N2/8/8 w - - 0 1");
+    assert_eq!(board.get_result(), ResultType::Draw(DrawReason::InsufficientMaterial));
+
+    // bishops on light squares
+    let board = Board::from_fen("8/5b2/3k4/8/8/3K4/8/3B4 w - - 0 1");
+    assert_eq!(board.get_result(), ResultType::Draw(DrawReason::InsufficientMaterial));
+
+    let board = Board::from_fen("8/8/3k4/8/8/3K1P2/8/8 w - - 0 1");
+    assert_eq!(board.get_result(), ResultType::None);
+}
+
+#[test]
+fn test_checkmate_result_tag() {
+    let board = Board::from_fen("2k5/1ppp4/pn5B/8/8/8/1Q3PPP/4r1K1 w - - 0 1");
+
+    assert_eq!(board.get_result(), ResultType::BlackCheckmate);
+    assert_eq!(board.get_result().to_pgn(), "0-1");
+}
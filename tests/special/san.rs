@@ -0,0 +1,176 @@
+use mchess::board::Board;
+use mchess::moves::{Move, SanError};
+use mchess::piece::PieceType;
+
+fn uci(san: &str, board: &Board) -> String {
+    format!("{:?}", Move::from_san(san, board).unwrap())
+}
+
+#[test]
+fn test_from_san_basic() {
+    let board = Board::startpos();
+
+    assert_eq!(uci("e4", &board), "e2e4");
+    assert_eq!(uci("Nf3", &board), "g1f3");
+    assert_eq!(uci("Nc3", &board), "b1c3");
+    assert_eq!(uci("a3", &board), "a2a3");
+}
+
+#[test]
+fn test_from_san_variants() {
+    let board = Board::startpos();
+
+    for san in ["Nf3+", "Nf3#", "Nf3!?", "Nf3??", "Nxf3", "Ngf3", "N1f3", "Ng1f3", "Ng1-f3", " Nf3 "] {
+        assert_eq!(uci(san, &board), "g1f3", "Failed to parse {}", san);
+    }
+
+    assert_eq!(uci("e2e4", &board), "e2e4");
+    assert_eq!(uci("e2-e4", &board), "e2e4");
+}
+
+#[test]
+fn test_from_san_captures() {
+    let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2");
+
+    assert_eq!(uci("exd5", &board), "e4d5");
+    assert_eq!(uci("ed5", &board), "e4d5");
+    assert_eq!(uci("e4d5", &board), "e4d5");
+    assert_eq!(uci("e4xd5", &board), "e4d5");
+
+    let board = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+
+    assert_eq!(uci("exf6", &board), "e5f6");
+    assert_eq!(uci("exf6 e.p.", &board), "e5f6");
+}
+
+#[test]
+fn test_from_san_castling() {
+    let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+
+    assert_eq!(uci("O-O", &board), "e1g1");
+    assert_eq!(uci("0-0", &board), "e1g1");
+    assert_eq!(uci("o-o", &board), "e1g1");
+    assert_eq!(uci("O-O-O", &board), "e1c1");
+    assert_eq!(uci("0-0-0+", &board), "e1c1");
+
+    let board = Board::startpos();
+    assert_eq!(Move::from_san("O-O", &board).err(), Some(SanError::IllegalMove("O-O".to_string())));
+}
+
+#[test]
+fn test_from_san_promotion() {
+    let board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
+
+    for (san, piece_type) in [
+        ("dxc8=Q", PieceType::Queen),
+        ("dxc8Q", PieceType::Queen),
+        ("dxc8=q", PieceType::Queen),
+        ("dxc8q", PieceType::Queen),
+        ("dxc8=N+", PieceType::Knight),
+        ("dc8R", PieceType::Rook),
+        ("d7c8b", PieceType::Bishop)
+    ] {
+        let m = Move::from_san(san, &board).unwrap();
+        assert_eq!(format!("{:?}", m.to), "c8");
+        assert_eq!(m.promote_to, Some(piece_type), "Wrong promotion for {}", san);
+    }
+
+    assert_eq!(Move::from_san("dxc8", &board).err(), Some(SanError::MissingPromotion("dxc8".to_string())));
+}
+
+#[test]
+fn test_from_san_errors() {
+    let board = Board::from_fen("1k6/8/8/8/8/8/8/R5RK w - - 0 1");
+
+    assert_eq!(Move::from_san("Rd1", &board).err(), Some(SanError::AmbiguousMove("Rd1".to_string())));
+    assert_eq!(Move::from_san("R1d1", &board).err(), Some(SanError::AmbiguousMove("R1d1".to_string())));
+    assert_eq!(uci("Rad1", &board), "a1d1");
+    assert_eq!(uci("Rgd1", &board), "g1d1");
+
+    assert_eq!(Move::from_san("Rb2", &board).err(), Some(SanError::IllegalMove("Rb2".to_string())));
+    assert_eq!(Move::from_san("Nf3", &board).err(), Some(SanError::IllegalMove("Nf3".to_string())));
+    assert_eq!(Move::from_san("", &board).err(), Some(SanError::InvalidSyntax("".to_string())));
+    assert_eq!(Move::from_san("Zf3", &board).err(), Some(SanError::InvalidSyntax("Zf3".to_string())));
+    assert_eq!(Move::from_san("Ri9", &board).err(), Some(SanError::InvalidSyntax("Ri9".to_string())));
+}
+
+fn find_move(board: &Board, uci: &str) -> Move {
+    board.generate_legal_moves(board.turn, false)
+        .into_iter()
+        .find(|m| m.to_uci() == uci)
+        .unwrap()
+}
+
+#[test]
+fn test_to_san_suffixes() {
+    let board = Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 2 3");
+
+    assert_eq!(find_move(&board, "f3f7").to_san(&board), "Qxf7#");
+    assert_eq!(find_move(&board, "c4f7").to_san(&board), "Bxf7+");
+    assert_eq!(find_move(&board, "g1e2").to_san(&board), "Ne2");
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert_eq!(find_move(&board, "e1g1").to_san(&board), "O-O");
+
+    let board = Board::from_fen("5k2/8/8/8/8/8/8/4K2R w K - 0 1");
+    assert_eq!(find_move(&board, "e1g1").to_san(&board), "O-O+");
+}
+
+#[test]
+fn test_to_san_disambiguation() {
+    let board = Board::from_fen("1k6/8/8/R7/8/8/8/R5K1 w - - 0 1");
+    assert_eq!(find_move(&board, "a1a3").to_san(&board), "R1a3");
+    assert_eq!(find_move(&board, "a5a3").to_san(&board), "R5a3");
+
+    let board = Board::from_fen("1k6/8/8/8/8/8/8/R4RK1 w - - 0 1");
+    assert_eq!(find_move(&board, "a1c1").to_san(&board), "Rac1");
+
+    let board = Board::from_fen("1k6/8/8/8/8/2N1N3/8/2N3K1 w - - 0 1");
+    assert_eq!(find_move(&board, "c3d1").to_san(&board), "Ncd1");
+    assert_eq!(find_move(&board, "e3d1").to_san(&board), "Ned1");
+    assert_eq!(find_move(&board, "c3e2").to_san(&board), "N3e2");
+    assert_eq!(find_move(&board, "c1e2").to_san(&board), "N1e2");
+
+    let board = Board::from_fen("1k6/2N5/8/8/8/2N1N3/8/6K1 w - - 0 1");
+    assert_eq!(find_move(&board, "c3d5").to_san(&board), "Nc3d5");
+}
+
+#[test]
+fn test_to_lan_and_uci() {
+    let board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
+
+    let m = find_move(&board, "d7c8q");
+    assert_eq!(m.to_uci(), "d7c8q");
+    assert_eq!(m.to_lan(&board), "d7xc8=Q");
+    assert_eq!(m.to_san(&board), "dxc8=Q");
+
+    let m = find_move(&board, "c4f7");
+    assert_eq!(m.to_uci(), "c4f7");
+    assert_eq!(m.to_lan(&board), "Bc4xf7");
+
+    let m = find_move(&board, "e1g1");
+    assert_eq!(m.to_uci(), "e1g1");
+    assert_eq!(m.to_lan(&board), "O-O");
+
+    let m = find_move(&board, "a2a4");
+    assert_eq!(m.to_lan(&board), "a2-a4");
+}
+
+#[test]
+fn test_san_round_trip() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10"
+    ] {
+        let board = Board::from_fen(fen);
+        for m in board.generate_legal_moves(board.turn, false) {
+            let san = m.to_san(&board);
+            let parsed = Move::from_san(&san, &board).unwrap_or_else(|e| panic!("{} in {}: {}", san, fen, e));
+            assert_eq!(parsed.to_uci(), m.to_uci(), "SAN {} in {}", san, fen);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use mchess::board::Board;
+use mchess::moves::Move;
+use mchess::search::Minimax;
+
+use crate::common::alg;
+
+fn see(fen: &str, san: &str) -> i32 {
+    let board = Board::from_fen(fen);
+    let m = Move::from_san(san, &board).unwrap();
+
+    board.see(&m)
+}
+
+#[test]
+fn test_simple_exchanges() {
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "Rxe5"), 100);
+    assert_eq!(see("4k3/8/8/3p4/8/8/3Q4/4K3 w - - 0 1", "Qxd5"), 100);
+    assert_eq!(see("4k3/2p5/3p4/8/8/8/3Q4/4K3 w - - 0 1", "Qxd6"), -800);
+    assert_eq!(see("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "Ra7"), 0);
+    assert_eq!(see("4k3/1p6/8/8/8/8/8/R3K3 w - - 0 1", "Ra6"), -500);
+}
+
+#[test]
+fn test_x_rays() {
+    // the second rook only joins in once the first one has left the file
+    assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5"), 100);
+    assert_eq!(see("3qk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "Rxd5"), -400);
+    assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "Nxe5"), -220);
+}
+
+#[test]
+fn test_special_moves() {
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6"), 100);
+    assert_eq!(see("4k3/2p5/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6"), 0);
+    // taking en passant opens the file for the rook behind
+    assert_eq!(see("3rk3/8/8/3pP3/8/8/8/3RK3 w - d6 0 1", "exd6"), 100);
+
+    assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q"), 800);
+    assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q"), -100);
+    assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "axb8=Q+"), 1300);
+
+    assert_eq!(see("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "O-O"), 0);
+}
+
+#[test]
+fn test_king_recaptures() {
+    assert_eq!(see("8/8/8/3k4/3p4/8/8/3RK3 w - - 0 1", "Rxd4+"), -400);
+    // the knight keeps the king from taking back
+    assert_eq!(see("8/8/8/3k4/3p4/5N2/8/3RK3 w - - 0 1", "Rxd4+"), 100);
+    assert_eq!(see("8/8/8/3k4/3p4/5N2/8/3RK3 w - - 0 1", "Nxd4"), 100);
+}
+
+#[test]
+fn test_see_ge_matches_see() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+        "3qk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1",
+        "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+        "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1"
+    ];
+
+    for fen in fens {
+        let board = Board::from_fen(fen);
+
+        for m in board.generate_legal_moves(board.turn, false) {
+            let see = board.see(&m);
+
+            for threshold in (-1500..=1500).step_by(50) {
+                assert_eq!(board.see_ge(&m, threshold), see >= threshold, "{} {:?} at {}", fen, m, threshold);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_attackers_to() {
+    let board = Board::from_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1");
+    let e5 = alg("e5");
+
+    let attackers = board.attackers_to(e5, board.bb.all_pieces);
+    let expected = [alg("d3"), alg("e2"), alg("d7"), alg("f6")].iter().fold(0u64, |acc, pos| acc | pos.to_bitboard());
+    assert_eq!(attackers, expected);
+
+    // with the rook and bishop gone, the queens behind them show up
+    let occupied = board.bb.all_pieces & !alg("e2").to_bitboard() & !alg("f6").to_bitboard();
+    assert_eq!(board.attackers_to(e5, occupied) & alg("e1").to_bitboard(), alg("e1").to_bitboard());
+    assert_eq!(board.attackers_to(e5, occupied) & alg("h8").to_bitboard(), alg("h8").to_bitboard());
+}
+
+#[test]
+fn test_move_scores_follow_position() {
+    let mut engine = Minimax::new();
+
+    // the same packed capture wins a pawn in the first position and loses the knight in the second
+    let mut free = Board::from_fen("4k3/8/8/4p3/8/5N2/8/4K3 w - - 0 1");
+    let mut defended = Board::from_fen("4k3/8/3p4/4p3/8/5N2/8/4K3 w - - 0 1");
+    let m = Move::from_san("Nxe5", &free).unwrap().pack();
+    assert_eq!(Move::from_san("Nxe5", &defended).unwrap().pack(), m);
+
+    let winning = engine.evaluate_move(m, &mut free, 0);
+    let losing = engine.evaluate_move(m, &mut defended, 0);
+
+    assert_eq!(losing, Minimax::evaluate_move_base(m, &mut defended));
+    assert!(losing < winning);
+}
@@ -0,0 +1,78 @@
+use mchess::board::{Board, ResultType};
+use mchess::moves::Move;
+use mchess::piece::PieceColor;
+use mchess::server::{game_state, GameState};
+
+fn state_after(fen: Option<&str>, moves: &[&str]) -> Result<GameState, String> {
+    let moves: Vec<String> = moves.iter().map(|m| m.to_string()).collect();
+    game_state(fen, &moves)
+}
+
+#[test]
+fn test_game_state_moves() {
+    let state = state_after(None, &[]).unwrap();
+
+    assert_eq!(state.fen, Board::startpos().to_fen());
+    assert_eq!(state.turn, PieceColor::White);
+    assert!(!state.check);
+    assert_eq!(state.result, ResultType::None);
+    assert_eq!(state.moves.len(), 20);
+
+    let e4 = state.moves.iter().find(|m| m.uci == "e2e4").unwrap();
+    assert_eq!(e4.san, "e4");
+    assert_eq!(e4.fen, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+    // every fen is the position the move leads to
+    for legal in &state.moves {
+        let mut board = Board::startpos();
+        let m = Move::from_uci(&legal.uci, &mut board).unwrap();
+        board.make_move(&m);
+        assert_eq!(legal.fen, board.to_fen(), "{}", legal.uci);
+    }
+}
+
+#[test]
+fn test_game_state_line() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let state = state_after(Some(fen), &["e1g1", "e8c8"]).unwrap();
+
+    assert_eq!(state.fen, "2kr3r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R4RK1 w - - 2 2");
+    assert_eq!(state.turn, PieceColor::White);
+    assert!(state.moves.iter().any(|m| m.uci == "e5f7" && m.san == "Nxf7"));
+}
+
+#[test]
+fn test_game_state_check_and_result() {
+    let state = state_after(None, &["e2e4", "f7f6", "d1h5"]).unwrap();
+    assert!(state.check);
+    assert_eq!(state.result, ResultType::None);
+    assert!(state.moves.iter().all(|m| m.uci == "g7g6"));
+    assert_eq!(state.moves[0].san, "g6");
+
+    let state = state_after(None, &["f2f3", "e7e5", "g2g4", "d8h4"]).unwrap();
+    assert!(state.check);
+    assert_eq!(state.result, ResultType::BlackCheckmate);
+    assert!(state.moves.is_empty());
+
+    let state = state_after(Some("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), &[]).unwrap();
+    assert!(!state.check);
+    assert_eq!(state.result, ResultType::Stalemate);
+    assert!(state.moves.is_empty());
+}
+
+#[test]
+fn test_game_state_errors() {
+    assert_eq!(state_after(None, &["e2e4", "e2e4"]).unwrap_err(), "Invalid move e2e4: illegal move 'e2e4'");
+    assert!(state_after(Some("8/8/8/8/8/8/8/8 w - - 0 1"), &[]).unwrap_err().starts_with("Invalid FEN"));
+}
+
+#[test]
+fn test_game_state_chess960() {
+    // castling is written as king takes rook
+    let state = state_after(Some("4k3/8/8/8/8/8/8/RK6 w Q - 0 1"), &[]).unwrap();
+
+    let castle = state.moves.iter().find(|m| m.san == "O-O-O").unwrap();
+    assert_eq!(castle.uci, "b1a1");
+    assert_eq!(castle.fen, "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+    assert!(state.moves.iter().any(|m| m.uci == "b1c1" && m.san == "Kc1"));
+}
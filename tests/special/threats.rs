@@ -0,0 +1,93 @@
+use mchess::board::Board;
+use mchess::moves::Position;
+use mchess::piece::{Piece, PieceColor, PieceType};
+use mchess::threats;
+
+use crate::common::alg;
+
+fn squares(pieces: &[Piece]) -> Vec<Position> {
+    let mut squares: Vec<Position> = pieces.iter().map(|piece| piece.pos).collect();
+    squares.sort_by_key(|pos| pos.x + pos.y * 8);
+    squares
+}
+
+#[test]
+fn test_square_control() {
+    let board = Board::from_fen("4k3/2n5/8/3p4/4P3/5N2/8/4K3 w - - 0 1");
+
+    let control = threats::square_control(&board, alg("d5"));
+    assert_eq!(control.piece.map(|piece| piece.piece_type), Some(PieceType::Pawn));
+    assert_eq!(squares(&control.attackers), vec![alg("e4")]);
+    assert_eq!(squares(&control.defenders), vec![alg("c7")]);
+
+    // empty squares are defended by the side to move
+    let control = threats::square_control(&board, alg("e5"));
+    assert!(control.piece.is_none());
+    assert!(control.attackers.is_empty());
+    assert_eq!(squares(&control.defenders), vec![alg("f3")]);
+
+    let json = serde_json::to_string(&control).unwrap();
+    assert!(json.starts_with(r#"{"square":"e5","piece":null,"attackers":[],"defenders":[{"piece_type":"Knight","color":"White","pos":"f3""#));
+}
+
+#[test]
+fn test_hanging_pieces() {
+    let board = Board::from_fen("4k3/8/8/3q1r2/4P3/2N5/8/4K3 b - - 0 1");
+
+    // the rook is taken by a pawn and the queen by a knight, defended or not
+    let hanging = threats::hanging_pieces(&board, PieceColor::Black);
+    let pieces: Vec<Piece> = hanging.iter().map(|h| h.piece).collect();
+    assert_eq!(squares(&pieces), vec![alg("d5"), alg("f5")]);
+
+    let queen = hanging.iter().find(|h| h.piece.piece_type == PieceType::Queen).unwrap();
+    assert_eq!(squares(&queen.attackers), vec![alg("e4"), alg("c3")]);
+    assert_eq!(squares(&queen.defenders), vec![alg("f5")]);
+
+    // the pawn is attacked by the queen but defended
+    assert!(threats::hanging_pieces(&board, PieceColor::White).is_empty());
+}
+
+#[test]
+fn test_pinned_pieces() {
+    let board = Board::from_fen("4k3/4r3/8/8/1b2N3/8/3NQ3/4K3 w - - 0 1");
+
+    let mut pins = threats::pinned_pieces(&board, PieceColor::White);
+    pins.sort_by_key(|pin| !pin.absolute);
+    assert_eq!(pins.len(), 2);
+
+    assert_eq!((pins[0].piece.pos, pins[0].pinner.pos, pins[0].target.pos), (alg("d2"), alg("b4"), alg("e1")));
+    assert!(pins[0].absolute);
+
+    assert_eq!((pins[1].piece.pos, pins[1].pinner.pos, pins[1].target.pos), (alg("e4"), alg("e7"), alg("e2")));
+    assert!(!pins[1].absolute);
+
+    assert!(threats::pinned_pieces(&board, PieceColor::Black).is_empty());
+}
+
+#[test]
+fn test_king_danger_squares() {
+    // the square behind the king stays covered by the checking rook
+    let board = Board::from_fen("8/R3k3/8/8/8/8/8/4K3 b - - 0 1");
+    assert_eq!(threats::king_danger_squares(&board, PieceColor::Black), vec![alg("f7"), alg("d7")]);
+
+    let board = Board::startpos();
+    assert!(threats::king_danger_squares(&board, PieceColor::White).is_empty());
+}
+
+#[test]
+fn test_discovered_attacks() {
+    let board = Board::from_fen("4k3/8/5r2/8/8/8/1N6/B3K3 w - - 0 1");
+
+    let discovered = threats::discovered_attacks(&board, PieceColor::White);
+    assert_eq!(discovered.len(), 1);
+    assert_eq!((discovered[0].piece.pos, discovered[0].slider.pos, discovered[0].target.pos), (alg("b2"), alg("a1"), alg("f6")));
+
+    let report = threats::threats(&board, PieceColor::Black);
+    assert_eq!(report.color, PieceColor::Black);
+    assert_eq!(report.discovered.len(), 1);
+    assert!(report.hanging.is_empty() && report.pins.is_empty());
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["discovered"][0]["target"]["pos"], "f6");
+    assert_eq!(json["color"], "Black");
+}
@@ -0,0 +1,88 @@
+use mchess::board::Board;
+use mchess::moves::{Move, MoveParseError};
+use mchess::piece::PieceType;
+use mchess::protocol::UciProtocol;
+
+#[test]
+fn test_from_uci() {
+    let mut board = Board::startpos();
+
+    let m = Move::from_uci("e2e4", &mut board).unwrap();
+    assert_eq!(m.to_uci(), "e2e4");
+    assert_eq!(m.piece_type, PieceType::Pawn);
+
+    let m = Move::from_uci("g1f3", &mut board).unwrap();
+    assert_eq!(m.to_uci(), "g1f3");
+    assert_eq!(m.piece_type, PieceType::Knight);
+
+    let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    assert_eq!(Move::from_uci("e1g1", &mut board).unwrap().to_uci(), "e1g1");
+    assert_eq!(Move::from_uci("e1c1", &mut board).unwrap().to_uci(), "e1c1");
+}
+
+#[test]
+fn test_from_uci_promotion() {
+    let mut board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
+
+    for (uci, piece_type) in [
+        ("d7c8q", PieceType::Queen),
+        ("d7c8r", PieceType::Rook),
+        ("d7c8b", PieceType::Bishop),
+        ("d7c8n", PieceType::Knight)
+    ] {
+        let m = Move::from_uci(uci, &mut board).unwrap();
+        assert_eq!(m.promote_to, Some(piece_type));
+        assert_eq!(m.to_uci(), uci);
+    }
+
+    assert_eq!(Move::from_uci("d7c8", &mut board).err(), Some(MoveParseError::IllegalMove("d7c8".to_string())));
+    assert_eq!(Move::from_uci("d7c8k", &mut board).err(), Some(MoveParseError::InvalidPromotion("d7c8k".to_string())));
+    assert_eq!(Move::from_uci("a2a3q", &mut board).err(), Some(MoveParseError::IllegalMove("a2a3q".to_string())));
+}
+
+#[test]
+fn test_from_uci_errors() {
+    let mut board = Board::startpos();
+
+    assert_eq!(Move::from_uci("0000", &mut board).err(), Some(MoveParseError::NullMove));
+    assert_eq!(Move::from_uci("e2e", &mut board).err(), Some(MoveParseError::InvalidLength("e2e".to_string())));
+    assert_eq!(Move::from_uci("e2e4e5", &mut board).err(), Some(MoveParseError::InvalidLength("e2e4e5".to_string())));
+    assert_eq!(Move::from_uci("e2é4", &mut board).err(), Some(MoveParseError::InvalidSquare("e2é4".to_string())));
+    assert_eq!(Move::from_uci("i2i4", &mut board).err(), Some(MoveParseError::InvalidSquare("i2i4".to_string())));
+    assert_eq!(Move::from_uci("e0e4", &mut board).err(), Some(MoveParseError::InvalidSquare("e0e4".to_string())));
+    assert_eq!(Move::from_uci("e2e5", &mut board).err(), Some(MoveParseError::IllegalMove("e2e5".to_string())));
+    assert_eq!(Move::from_uci("e7e5", &mut board).err(), Some(MoveParseError::IllegalMove("e7e5".to_string())));
+}
+
+#[test]
+fn test_position_moves() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
+
+    protocol.handle_position("position startpos moves e2e4 e7e5 g1f3", &mut output).unwrap();
+    assert!(output.is_empty(), "Unexpected output: {}", String::from_utf8(output).unwrap());
+
+    protocol.handle_position("position fen rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8 moves d7c8n", &mut output).unwrap();
+    assert!(output.is_empty(), "Unexpected output: {}", String::from_utf8(output).unwrap());
+
+    protocol.handle_position("position startpos moves e2e4 e2e4", &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.trim(), "info string Invalid move e2e4: illegal move 'e2e4'");
+}
+
+#[test]
+fn test_position_null_move() {
+    let mut protocol = UciProtocol::new();
+    let mut output = Vec::new();
+
+    // black passes, so white moves twice in a row
+    protocol.handle_position("position startpos moves e2e4 0000 d2d4", &mut output).unwrap();
+    assert!(output.is_empty(), "Unexpected output: {}", String::from_utf8(output).unwrap());
+    assert!(protocol.board().to_fen().starts_with("rnbqkbnr/pppppppp/8/8/3PP3/8/PPP2PPP/RNBQKBNR b"));
+    assert_eq!(protocol.game().sans(), vec!["e4", "--", "d4"]);
+
+    // passing out of check isn't allowed
+    protocol.handle_position("position startpos moves e2e4 f7f6 d1h5 0000", &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.trim(), "info string Invalid move 0000: null move '0000'");
+}
@@ -0,0 +1,87 @@
+use mchess::board::{Board, PositionError};
+use mchess::piece::PieceColor;
+
+use crate::common::alg;
+
+#[test]
+fn test_valid_positions() {
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "nrbkqbrn/pppppppp/8/8/8/8/PPPPPPPP/NRBKQBRN w GBgb - 0 1",
+        "QQQQQQQQ/8/8/8/8/8/8/k1K5 b - - 0 1"
+    ];
+
+    for fen in positions {
+        assert_eq!(Board::from_fen(fen).validate(), vec![], "{fen}");
+    }
+}
+
+#[test]
+fn test_piece_counts() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/PPPPPPPP/PPPPKPPP w - - 0 1");
+    let errors = board.validate();
+
+    assert!(errors.contains(&PositionError::TooManyPawns(PieceColor::White, 15)));
+    assert!(errors.contains(&PositionError::PawnOnBackRank(alg("a1"))));
+    assert_eq!(errors.iter().filter(|e| matches!(e, PositionError::PawnOnBackRank(_))).count(), 7);
+
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/3N4/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let errors = board.validate();
+
+    assert!(errors.contains(&PositionError::TooManyPieces(PieceColor::White, 17)));
+    assert!(errors.contains(&PositionError::ImpossiblePromotions(PieceColor::White)));
+
+    // two bishops on the same square color need a promotion
+    let board = Board::from_fen("4k3/8/8/8/8/8/PPPPPPPP/2B1KB2 w - - 0 1");
+    assert_eq!(board.validate(), vec![]);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/PPPPPPPP/3BKB2 w - - 0 1");
+    assert_eq!(board.validate(), vec![PositionError::ImpossiblePromotions(PieceColor::White)]);
+}
+
+#[test]
+fn test_kings() {
+    let board = Board::from_fen("8/8/8/3kK3/8/8/8/8 w - - 0 1");
+    assert!(board.validate().contains(&PositionError::KingsAdjacent));
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1");
+    assert_eq!(board.validate(), vec![]);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1");
+    assert_eq!(board.validate(), vec![PositionError::OpponentInCheck]);
+}
+
+#[test]
+fn test_castling_rights() {
+    let board = Board::from_fen("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1");
+    let errors = board.validate();
+
+    assert_eq!(errors, vec![
+        PositionError::InvalidCastlingRights(PieceColor::White, false),
+        PositionError::InvalidCastlingRights(PieceColor::Black, true)
+    ]);
+
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/4K3/R6R w KQkq - 0 1");
+    let errors = board.validate();
+
+    assert!(errors.contains(&PositionError::InvalidCastlingRights(PieceColor::White, true)));
+    assert!(errors.contains(&PositionError::InvalidCastlingRights(PieceColor::White, false)));
+    assert!(!errors.iter().any(|e| matches!(e, PositionError::InvalidCastlingRights(PieceColor::Black, _))));
+}
+
+#[test]
+fn test_en_passant() {
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(board.validate(), vec![]);
+
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq d3 0 1");
+    assert_eq!(board.validate(), vec![PositionError::InvalidEnPassant(alg("d3"))]);
+
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1");
+    assert_eq!(board.validate(), vec![PositionError::InvalidEnPassant(alg("e3"))]);
+
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/4P3/PPP2PPP/RNBQKBNR b KQkq e3 0 1");
+    assert!(board.validate().contains(&PositionError::InvalidEnPassant(alg("e3"))));
+}
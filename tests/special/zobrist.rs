@@ -0,0 +1,73 @@
+use mchess::board::Board;
+use mchess::moves::Move;
+
+// key test vectors from the Polyglot book format specification
+const VECTORS: [(&str, &str, u64); 9] = [
+    ("", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+    ("e2e4", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+    ("e2e4 d7d5", "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+    ("e2e4 d7d5 e4e5", "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+    ("e2e4 d7d5 e4e5 f7f5", "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+    ("e2e4 d7d5 e4e5 f7f5 e1e2", "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+    ("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7", "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+    ("a2a4 b7b5 h2h4 b5b4 c2c4", "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+    ("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3", "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560)
+];
+
+#[test]
+fn test_polyglot_vectors() {
+    for (moves, fen, key) in VECTORS {
+        let board = Board::from_fen(fen);
+        assert_eq!(board.polyglot_key(), key, "{fen}");
+        assert_eq!(board.hash, key, "{fen}");
+
+        let mut board = Board::startpos();
+        for uci in moves.split_whitespace() {
+            let m = Move::from_uci(uci, &mut board).unwrap();
+            board.make_move(&m);
+        }
+
+        assert_eq!(board.hash, key, "{moves}");
+        assert_eq!(board.polyglot_key(), key, "{moves}");
+    }
+}
+
+#[test]
+fn test_en_passant_only_if_capturable() {
+    let with_target = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    let without_target = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(with_target.hash, without_target.hash);
+
+    let with_target = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+    let without_target = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3");
+    assert_ne!(with_target.hash, without_target.hash);
+}
+
+#[test]
+fn test_incremental_hash() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "nrbkqbrn/pppppppp/8/8/8/8/PPPPPPPP/NRBKQBRN w GBgb - 0 1"
+    ];
+
+    for fen in fens {
+        let mut board = Board::from_fen(fen);
+
+        for m in board.get_total_legal_moves(None) {
+            let start = board.hash;
+            let history = board.make_move(&m);
+            assert_eq!(board.hash, board.polyglot_key(), "{fen} {}", m.to_uci());
+
+            for reply in board.get_total_legal_moves(None) {
+                let reply_history = board.make_move(&reply);
+                assert_eq!(board.hash, board.polyglot_key(), "{fen} {} {}", m.to_uci(), reply.to_uci());
+                board.unmake_move(&reply, &reply_history);
+            }
+
+            board.unmake_move(&m, &history);
+            assert_eq!(board.hash, start);
+        }
+    }
+}